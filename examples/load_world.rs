@@ -1,3 +1,38 @@
+use std::path::PathBuf;
+
+use rnbt::{read_nbt, Region};
+
 pub fn main() {
-    println!("Hello, world!");
+    let world = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .expect("usage: load_world <world dir>"),
+    );
+
+    let level = read_nbt(&mut std::fs::File::open(world.join("level.dat")).unwrap()).unwrap();
+    if let Some(name) = level
+        .get_path(&["Data", "LevelName"])
+        .and_then(|f| f.as_string())
+    {
+        println!("loading world '{}'", name);
+    }
+
+    let mut regions: Vec<_> = std::fs::read_dir(world.join("region"))
+        .unwrap()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mca"))
+        .collect();
+    regions.sort();
+
+    for path in regions {
+        let mut region = Region::open(&path).unwrap();
+        let mut chunks = 0;
+        for chunk in region.chunks() {
+            match chunk {
+                Ok(_) => chunks += 1,
+                Err(e) => eprintln!("{}: {:?}", path.display(), e),
+            }
+        }
+        println!("{}: {} chunks", path.display(), chunks);
+    }
 }
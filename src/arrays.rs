@@ -0,0 +1,81 @@
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+// Newtype struct names the serializer recognizes to emit typed arrays instead of lists.
+pub(crate) const BYTE_ARRAY_TOKEN: &str = "__rnbt_byte_array";
+pub(crate) const INT_ARRAY_TOKEN: &str = "__rnbt_int_array";
+pub(crate) const LONG_ARRAY_TOKEN: &str = "__rnbt_long_array";
+
+macro_rules! typed_array {
+    ($name:ident, $module:ident, $token:ident, $t:ty, $doc:literal) => {
+        #[doc = concat!("Serializes as `", $doc, "` instead of a `List`.")]
+        #[derive(Debug, Clone, Default, PartialEq)]
+        pub struct $name(pub Vec<$t>);
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $module::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                $module::deserialize(deserializer).map($name)
+            }
+        }
+
+        impl From<Vec<$t>> for $name {
+            fn from(value: Vec<$t>) -> Self {
+                $name(value)
+            }
+        }
+
+        #[doc = concat!("Use with `#[serde(with = \"rnbt::", stringify!($module), "\")]` to store a")]
+        #[doc = concat!("`Vec<", stringify!($t), ">` field as `", $doc, "`.")]
+        pub mod $module {
+            use super::*;
+
+            pub fn serialize<S: Serializer>(value: &[$t], serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_newtype_struct($token, value)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Vec<$t>, D::Error> {
+                deserializer.deserialize_newtype_struct($token, ArrayVisitor)
+            }
+
+            struct ArrayVisitor;
+
+            impl<'de> serde::de::Visitor<'de> for ArrayVisitor {
+                type Value = Vec<$t>;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    write!(f, "{}", $doc)
+                }
+
+                fn visit_newtype_struct<D: Deserializer<'de>>(
+                    self,
+                    deserializer: D,
+                ) -> Result<Self::Value, D::Error> {
+                    Vec::<$t>::deserialize(deserializer)
+                }
+
+                fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                    self,
+                    mut seq: A,
+                ) -> Result<Self::Value, A::Error> {
+                    let mut values = Vec::new();
+                    while let Some(value) = seq.next_element()? {
+                        values.push(value);
+                    }
+                    Ok(values)
+                }
+            }
+        }
+    };
+}
+
+typed_array!(ByteArray, byte_array, BYTE_ARRAY_TOKEN, u8, "ByteArray");
+typed_array!(IntArray, int_array, INT_ARRAY_TOKEN, i32, "IntArray");
+typed_array!(LongArray, long_array, LONG_ARRAY_TOKEN, i64, "LongArray");
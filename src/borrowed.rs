@@ -0,0 +1,493 @@
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+use crate::decode::{Decoder, ReadLimit, ReadOptions};
+use crate::encode::Encoding;
+use crate::error::NbtError;
+use crate::field::NbtField;
+use crate::mutf8;
+use crate::value::*;
+
+/// A zero-copy view of an NBT field inside a byte slice.
+///
+/// The whole structure is validated once by [`NbtRef::new`], afterwards compounds and lists are
+/// navigated lazily and arrays are returned as slices of the input. Strings borrow from the input
+/// as well, unless they contain NUL or characters outside the BMP.
+///
+/// Only big-endian (Java) data can be viewed, the `encoding` of [`ReadOptions`] is ignored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NbtRef<'a> {
+    name: &'a [u8],
+    tag: u8,
+    payload: &'a [u8],
+}
+
+/// A view of the elements of a `TAG_List`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ListRef<'a> {
+    tag: u8,
+    len: usize,
+    data: &'a [u8],
+}
+
+/// A view of big-endian numbers, as found in int and long arrays and in lists of numbers.
+#[derive(Clone, Copy, PartialEq)]
+pub struct BeSlice<'a, T> {
+    bytes: &'a [u8],
+    _marker: PhantomData<T>,
+}
+
+// ---- Constructors -------------------------------------------------------------------------------
+impl<'a> NbtRef<'a> {
+    /// Validates an uncompressed NBT blob and returns a view of its root field.
+    pub fn new(bytes: &'a [u8]) -> Result<NbtRef<'a>, NbtError> {
+        Self::with_options(bytes, &ReadOptions::default())
+    }
+
+    /// Like [`NbtRef::new`], enforcing the depth and size limits, the string decoding mode and the
+    /// root requirement of `options`. Nothing is allocated, so `max_alloc` does not apply, and
+    /// duplicate names are not checked: lookups find the first of them.
+    pub fn with_options(bytes: &'a [u8], options: &ReadOptions) -> Result<NbtRef<'a>, NbtError> {
+        let tag = *bytes.first().ok_or_else(eof)?;
+        if options.require_compound_root && tag != TAG_COMPOUND {
+            return Err(NbtError::RootNotCompoundError);
+        }
+        if tag == TAG_END {
+            return Ok(NbtRef {
+                name: &[],
+                tag,
+                payload: &[],
+            });
+        }
+        let name_len = read_u16(bytes, 1)? as usize;
+        let checks = Checks {
+            max_depth: options.max_depth,
+            strings: !options.lenient_strings,
+        };
+        let name = str_at(bytes, 3, name_len, checks)?;
+        let start = 3 + name_len;
+        let len = payload_len(tag, &bytes[start..], 0, checks)?;
+        match options.max_size {
+            Some(max) if (start + len) as u64 > max => {
+                Err(NbtError::LimitExceeded(ReadLimit::Size(max)))
+            }
+            _ => Ok(NbtRef {
+                name,
+                tag,
+                payload: &bytes[start..start + len],
+            }),
+        }
+    }
+}
+
+// ---- Element Access -----------------------------------------------------------------------------
+impl<'a> NbtRef<'a> {
+    /// The name, decoded from modified UTF-8. It only allocates for names containing NUL or
+    /// characters outside the BMP.
+    pub fn name(&self) -> Cow<'a, str> {
+        decode(self.name)
+    }
+
+    pub fn tag(&self) -> u8 {
+        self.tag
+    }
+
+    /// The raw big-endian payload of this field.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    pub fn get(&self, name: &str) -> Option<NbtRef<'a>> {
+        self.iter().find(|field| field.name() == name)
+    }
+
+    pub fn get_path(&self, path: &[&str]) -> Option<NbtRef<'a>> {
+        path.iter().try_fold(*self, |field, name| field.get(name))
+    }
+
+    /// Iterates over the entries of a compound, or nothing for any other tag.
+    pub fn iter(&self) -> CompoundIter<'a> {
+        CompoundIter {
+            data: if self.tag == TAG_COMPOUND {
+                self.payload
+            } else {
+                &[]
+            },
+        }
+    }
+
+    /// Copies this field into an owned field.
+    pub fn to_field(&self) -> Result<NbtField, NbtError> {
+        self.to_field_with_options(&ReadOptions::default())
+    }
+
+    /// Like [`NbtRef::to_field`], applying `max_alloc` and `duplicate_keys` of `options`. Pass the
+    /// options the view was built with.
+    pub fn to_field_with_options(&self, options: &ReadOptions) -> Result<NbtField, NbtError> {
+        Ok(NbtField {
+            name: self.name().into_owned(),
+            value: self.to_value_with_options(options)?,
+        })
+    }
+
+    /// Copies this field into an owned value.
+    pub fn to_value(&self) -> Result<NbtValue, NbtError> {
+        self.to_value_with_options(&ReadOptions::default())
+    }
+
+    /// Like [`NbtRef::to_value`], applying `max_alloc` and `duplicate_keys` of `options`.
+    pub fn to_value_with_options(&self, options: &ReadOptions) -> Result<NbtValue, NbtError> {
+        if self.tag == TAG_END {
+            return Ok(NbtValue::End);
+        }
+        // strings were checked by the constructor already
+        let options = options
+            .clone()
+            .encoding(Encoding::BigEndian)
+            .lenient_strings(true);
+        Decoder::new(self.payload, &options).read_payload(self.tag)
+    }
+
+    // ---- Convenience Access ---------------------------------------------------------------------
+    pub fn as_byte(&self) -> Option<u8> {
+        self.scalar(TAG_BYTE).map(|p| p[0])
+    }
+    pub fn as_short(&self) -> Option<i16> {
+        self.scalar(TAG_SHORT).map(BeElement::from_be)
+    }
+    pub fn as_i32(&self) -> Option<i32> {
+        self.scalar(TAG_INT).map(BeElement::from_be)
+    }
+    pub fn as_long(&self) -> Option<i64> {
+        self.scalar(TAG_LONG).map(BeElement::from_be)
+    }
+    pub fn as_float(&self) -> Option<f32> {
+        self.scalar(TAG_FLOAT).map(BeElement::from_be)
+    }
+    pub fn as_double(&self) -> Option<f64> {
+        self.scalar(TAG_DOUBLE).map(BeElement::from_be)
+    }
+    pub fn as_str(&self) -> Option<Cow<'a, str>> {
+        self.scalar(TAG_STRING).map(|p| decode(&p[2..]))
+    }
+    pub fn as_byte_array(&self) -> Option<&'a [u8]> {
+        self.scalar(TAG_BYTE_ARRAY).map(|p| &p[4..])
+    }
+    pub fn as_int_array(&self) -> Option<BeSlice<'a, i32>> {
+        self.scalar(TAG_INT_ARRAY).map(|p| BeSlice::new(&p[4..]))
+    }
+    pub fn as_long_array(&self) -> Option<BeSlice<'a, i64>> {
+        self.scalar(TAG_LONG_ARRAY).map(|p| BeSlice::new(&p[4..]))
+    }
+    pub fn as_list(&self) -> Option<ListRef<'a>> {
+        self.scalar(TAG_LIST).map(|p| ListRef {
+            tag: p[0],
+            len: read_u32_unchecked(p, 1) as usize,
+            data: &p[5..],
+        })
+    }
+
+    fn scalar(&self, tag: u8) -> Option<&'a [u8]> {
+        (self.tag == tag).then_some(self.payload)
+    }
+}
+
+pub struct CompoundIter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for CompoundIter<'a> {
+    type Item = NbtRef<'a>;
+
+    fn next(&mut self) -> Option<NbtRef<'a>> {
+        let tag = *self.data.first()?;
+        if tag == TAG_END {
+            return None;
+        }
+        let name_len = read_u16(self.data, 1).ok()? as usize;
+        let name = str_at(self.data, 3, name_len, TRUSTED).ok()?;
+        let start = 3 + name_len;
+        let len = payload_len(tag, &self.data[start..], 0, TRUSTED).ok()?;
+        let payload = &self.data[start..start + len];
+        self.data = &self.data[start + len..];
+        Some(NbtRef { name, tag, payload })
+    }
+}
+
+// ---- Lists --------------------------------------------------------------------------------------
+impl<'a> ListRef<'a> {
+    pub fn element_tag(&self) -> u8 {
+        self.tag
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Element `index`, found in constant time for lists of numbers and by skipping over the
+    /// preceding elements otherwise.
+    pub fn get(&self, index: usize) -> Option<NbtRef<'a>> {
+        if index >= self.len {
+            return None;
+        }
+        match fixed_size(self.tag) {
+            Some(size) => Some(NbtRef {
+                name: &[],
+                tag: self.tag,
+                payload: &self.data[index * size..(index + 1) * size],
+            }),
+            None => self.iter().nth(index),
+        }
+    }
+
+    pub fn iter(&self) -> ListIter<'a> {
+        ListIter {
+            tag: self.tag,
+            remaining: self.len,
+            data: self.data,
+        }
+    }
+
+    pub fn as_short_slice(&self) -> Option<BeSlice<'a, i16>> {
+        self.numbers(TAG_SHORT)
+    }
+    pub fn as_int_slice(&self) -> Option<BeSlice<'a, i32>> {
+        self.numbers(TAG_INT)
+    }
+    pub fn as_long_slice(&self) -> Option<BeSlice<'a, i64>> {
+        self.numbers(TAG_LONG)
+    }
+    pub fn as_float_slice(&self) -> Option<BeSlice<'a, f32>> {
+        self.numbers(TAG_FLOAT)
+    }
+    pub fn as_double_slice(&self) -> Option<BeSlice<'a, f64>> {
+        self.numbers(TAG_DOUBLE)
+    }
+
+    fn numbers<T: BeElement>(&self, tag: u8) -> Option<BeSlice<'a, T>> {
+        (self.tag == tag).then(|| BeSlice::new(&self.data[..self.len * T::SIZE]))
+    }
+}
+
+impl<'a> IntoIterator for ListRef<'a> {
+    type Item = NbtRef<'a>;
+    type IntoIter = ListIter<'a>;
+
+    fn into_iter(self) -> ListIter<'a> {
+        self.iter()
+    }
+}
+
+pub struct ListIter<'a> {
+    tag: u8,
+    remaining: usize,
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for ListIter<'a> {
+    type Item = NbtRef<'a>;
+
+    fn next(&mut self) -> Option<NbtRef<'a>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let len = payload_len(self.tag, self.data, 0, TRUSTED).ok()?;
+        let payload = &self.data[..len];
+        self.data = &self.data[len..];
+        self.remaining -= 1;
+        Some(NbtRef {
+            name: &[],
+            tag: self.tag,
+            payload,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+// ---- Big-endian slices --------------------------------------------------------------------------
+mod private {
+    pub trait Sealed {}
+}
+
+/// Numbers that can be decoded from a [`BeSlice`].
+pub trait BeElement: Copy + 'static + private::Sealed {
+    const SIZE: usize;
+    fn from_be(bytes: &[u8]) -> Self;
+}
+
+macro_rules! be_element {
+    ($($t:ty),*) => {$(
+        impl private::Sealed for $t {}
+        impl BeElement for $t {
+            const SIZE: usize = std::mem::size_of::<$t>();
+            fn from_be(bytes: &[u8]) -> Self {
+                <$t>::from_be_bytes(bytes[..Self::SIZE].try_into().unwrap())
+            }
+        }
+    )*};
+}
+
+be_element!(i16, i32, i64, f32, f64);
+
+impl<'a, T: BeElement> BeSlice<'a, T> {
+    fn new(bytes: &'a [u8]) -> Self {
+        BeSlice {
+            bytes,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len() / T::SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.bytes
+            .get(index * T::SIZE..(index + 1) * T::SIZE)
+            .map(T::from_be)
+    }
+
+    /// The raw big-endian bytes.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = T> + 'a {
+        self.bytes.chunks_exact(T::SIZE).map(T::from_be)
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().collect()
+    }
+}
+
+impl<T: BeElement + std::fmt::Debug> std::fmt::Debug for BeSlice<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// ---- Helper functions ---------------------------------------------------------------------------
+fn eof() -> NbtError {
+    NbtError::UnexpectedEof
+}
+
+fn fixed_size(tag: u8) -> Option<usize> {
+    match tag {
+        TAG_END => Some(0),
+        TAG_BYTE => Some(1),
+        TAG_SHORT => Some(2),
+        TAG_INT | TAG_FLOAT => Some(4),
+        TAG_LONG | TAG_DOUBLE => Some(8),
+        _ => None,
+    }
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Result<u16, NbtError> {
+    let b = bytes.get(at..at + 2).ok_or_else(eof)?;
+    Ok(u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn read_len(bytes: &[u8], at: usize) -> Result<usize, NbtError> {
+    let b = bytes.get(at..at + 4).ok_or_else(eof)?;
+    let len = i32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+    usize::try_from(len).map_err(|_| NbtError::NegativeLength(len))
+}
+
+fn read_u32_unchecked(bytes: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+/// What [`payload_len`] validates besides the structure.
+#[derive(Clone, Copy)]
+struct Checks {
+    max_depth: usize,
+    strings: bool,
+}
+
+/// For data that has been validated before.
+const TRUSTED: Checks = Checks {
+    max_depth: usize::MAX,
+    strings: false,
+};
+
+fn str_at(bytes: &[u8], at: usize, len: usize, checks: Checks) -> Result<&[u8], NbtError> {
+    let b = bytes.get(at..at + len).ok_or_else(eof)?;
+    if checks.strings {
+        mutf8::decode(b, false)?;
+    }
+    Ok(b)
+}
+
+fn decode(bytes: &[u8]) -> Cow<'_, str> {
+    mutf8::decode(bytes, true).unwrap_or_default()
+}
+
+/// Validates the payload of a `tag` at the start of `bytes` and returns its length.
+fn payload_len(tag: u8, bytes: &[u8], depth: usize, checks: Checks) -> Result<usize, NbtError> {
+    if (tag == TAG_LIST || tag == TAG_COMPOUND) && depth >= checks.max_depth {
+        return Err(NbtError::LimitExceeded(ReadLimit::Depth(checks.max_depth)));
+    }
+    let len = match tag {
+        TAG_BYTE_ARRAY => 4 + read_len(bytes, 0)?,
+        TAG_INT_ARRAY => 4 + read_len(bytes, 0)? * 4,
+        TAG_LONG_ARRAY => 4 + read_len(bytes, 0)? * 8,
+        TAG_STRING => {
+            let len = read_u16(bytes, 0)? as usize;
+            str_at(bytes, 2, len, checks)?;
+            2 + len
+        }
+        TAG_LIST => {
+            let element_tag = *bytes.first().ok_or_else(eof)?;
+            let count = read_len(bytes, 1)?;
+            if element_tag > TAG_LONG_ARRAY || (element_tag == TAG_END && count > 0) {
+                return Err(NbtError::InvalidListType(element_tag));
+            }
+            match fixed_size(element_tag) {
+                Some(size) => 5 + count * size,
+                None => {
+                    let mut pos = 5;
+                    for _ in 0..count {
+                        pos += payload_len(
+                            element_tag,
+                            &bytes[pos.min(bytes.len())..],
+                            depth + 1,
+                            checks,
+                        )?;
+                    }
+                    pos
+                }
+            }
+        }
+        TAG_COMPOUND => {
+            let mut pos = 0;
+            loop {
+                let tag = *bytes.get(pos).ok_or_else(eof)?;
+                pos += 1;
+                if tag == TAG_END {
+                    break pos;
+                }
+                let name_len = read_u16(bytes, pos)? as usize;
+                str_at(bytes, pos + 2, name_len, checks)?;
+                pos += 2 + name_len;
+                pos += payload_len(tag, &bytes[pos.min(bytes.len())..], depth + 1, checks)?;
+            }
+        }
+        _ => fixed_size(tag).ok_or(NbtError::UnknownTag(tag))?,
+    };
+    if len > bytes.len() {
+        return Err(eof());
+    }
+    Ok(len)
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use crate::entry::Entry;
+use crate::field::NbtField;
+use crate::value::NbtValue;
+
+/// The fields of a `TAG_Compound`, in the order they were read or inserted, indexed by name.
+///
+/// Dereferences to a slice of fields for iteration. Names cannot be changed in place, so that
+/// the index stays valid: mutable access only reaches the values.
+#[derive(Debug, Clone, Default)]
+pub struct NbtCompound {
+    fields: Vec<NbtField>,
+    index: HashMap<String, usize>,
+}
+
+impl NbtCompound {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        NbtCompound {
+            fields: Vec::with_capacity(capacity),
+            index: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NbtField> {
+        self.index.get(name).map(|i| &self.fields[*i])
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut NbtValue> {
+        self.index.get(name).map(|i| &mut self.fields[*i].value)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// Replaces the field of the same name in place or appends the field. Returns the replaced
+    /// field.
+    pub fn insert(&mut self, field: NbtField) -> Option<NbtField> {
+        match self.index.get(&field.name) {
+            Some(i) => Some(std::mem::replace(&mut self.fields[*i], field)),
+            None => {
+                self.index.insert(field.name.clone(), self.fields.len());
+                self.fields.push(field);
+                None
+            }
+        }
+    }
+
+    /// Removes a field, keeping the order of the remaining fields.
+    pub fn remove(&mut self, name: &str) -> Option<NbtField> {
+        let i = self.index.remove(name)?;
+        let field = self.fields.remove(i);
+        for later in &self.fields[i..] {
+            *self.index.get_mut(&later.name).unwrap() -= 1;
+        }
+        Some(field)
+    }
+
+    /// Removes a field in O(1), moving the last field into its place.
+    pub fn swap_remove(&mut self, name: &str) -> Option<NbtField> {
+        let i = self.index.remove(name)?;
+        let field = self.fields.swap_remove(i);
+        if let Some(moved) = self.fields.get(i) {
+            self.index.insert(moved.name.clone(), i);
+        }
+        Some(field)
+    }
+
+    /// The field `name`, for in-place updates and inserts.
+    pub fn entry(&mut self, name: &str) -> Entry<'_> {
+        Entry::new(self, name)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut NbtValue> {
+        self.fields.iter_mut().map(|f| &mut f.value)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut NbtValue)> {
+        self.fields
+            .iter_mut()
+            .map(|f| (f.name.as_str(), &mut f.value))
+    }
+
+    pub fn into_vec(self) -> Vec<NbtField> {
+        self.fields
+    }
+
+    pub(crate) fn index_of(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    /// Bytes a field named `name` adds to the index, for `ReadOptions::max_alloc`.
+    pub(crate) fn index_cost(name: &str) -> usize {
+        name.len() + std::mem::size_of::<(String, usize)>()
+    }
+
+    pub(crate) fn value_at(&mut self, i: usize) -> &mut NbtValue {
+        &mut self.fields[i].value
+    }
+}
+
+impl Deref for NbtCompound {
+    type Target = [NbtField];
+
+    fn deref(&self) -> &[NbtField] {
+        &self.fields
+    }
+}
+
+/// Compares the fields in order, as they would be written.
+impl PartialEq for NbtCompound {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields == other.fields
+    }
+}
+
+/// Later fields replace earlier fields of the same name, see [`NbtCompound::insert`].
+impl From<Vec<NbtField>> for NbtCompound {
+    fn from(fields: Vec<NbtField>) -> Self {
+        fields.into_iter().collect()
+    }
+}
+
+impl<const N: usize> From<[NbtField; N]> for NbtCompound {
+    fn from(fields: [NbtField; N]) -> Self {
+        fields.into_iter().collect()
+    }
+}
+
+impl FromIterator<NbtField> for NbtCompound {
+    fn from_iter<I: IntoIterator<Item = NbtField>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut compound = NbtCompound::with_capacity(iter.size_hint().0);
+        for field in iter {
+            compound.insert(field);
+        }
+        compound
+    }
+}
+
+impl IntoIterator for NbtCompound {
+    type Item = NbtField;
+    type IntoIter = std::vec::IntoIter<NbtField>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a NbtCompound {
+    type Item = &'a NbtField;
+    type IntoIter = std::slice::Iter<'a, NbtField>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.iter()
+    }
+}
@@ -0,0 +1,251 @@
+use std::io::{Cursor, Read, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use twox_hash::XxHash32;
+
+use crate::error::NbtError;
+
+pub const DEFAULT_COMPRESSION_LEVEL: u32 = 6;
+
+const GZIP_MAGIC: u8 = 0x1f;
+const ZLIB_MAGIC: u8 = 0x78;
+
+// Framing of lz4-java's `LZ4BlockOutputStream`, which Minecraft uses for LZ4 compressed chunks.
+const LZ4_MAGIC: &[u8; 8] = b"LZ4Block";
+const LZ4_METHOD_RAW: u8 = 0x10;
+const LZ4_METHOD_LZ4: u8 = 0x20;
+const LZ4_BLOCK_SIZE: usize = 1 << 16;
+const LZ4_MAX_BLOCK_SIZE: i32 = 1 << 25;
+const LZ4_CHECKSUM_SEED: u32 = 0x9747_b28c;
+
+/// Compression wrapped around an NBT stream. The payload of `Gzip` and `Zlib` is the compression
+/// level (0-9) used when writing, it is ignored when reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip(u32),
+    Zlib(u32),
+    Lz4,
+}
+
+impl Compression {
+    pub fn gzip() -> Compression {
+        Compression::Gzip(DEFAULT_COMPRESSION_LEVEL)
+    }
+
+    pub fn zlib() -> Compression {
+        Compression::Zlib(DEFAULT_COMPRESSION_LEVEL)
+    }
+
+    /// Guesses the compression from the first byte of a stream.
+    ///
+    /// Uncompressed NBT always starts with a tag id (0..=12), which can neither be confused with
+    /// the gzip magic number, the zlib header Java emits nor the `LZ4Block` magic.
+    pub fn detect(first_byte: u8) -> Compression {
+        match first_byte {
+            GZIP_MAGIC => Compression::gzip(),
+            ZLIB_MAGIC => Compression::zlib(),
+            b if b == LZ4_MAGIC[0] => Compression::Lz4,
+            _ => Compression::None,
+        }
+    }
+}
+
+// ---- Helper functions ---------------------------------------------------------------------------
+pub(crate) fn decoder<'a, R: Read + 'a>(r: R, compression: Compression) -> Box<dyn Read + 'a> {
+    match compression {
+        Compression::None => Box::new(r),
+        Compression::Gzip(_) => Box::new(GzDecoder::new(r)),
+        Compression::Zlib(_) => Box::new(ZlibDecoder::new(r)),
+        Compression::Lz4 => Box::new(Lz4BlockDecoder::new(r)),
+    }
+}
+
+/// Peeks at the first byte of `r` and wraps it into the matching decoder.
+pub(crate) fn auto_decoder<'a, R: Read + 'a>(mut r: R) -> Result<Box<dyn Read + 'a>, NbtError> {
+    let mut first = [0u8; 1];
+    r.read_exact(&mut first)?;
+    let compression = Compression::detect(first[0]);
+    Ok(decoder(Cursor::new(first).chain(r), compression))
+}
+
+pub(crate) fn encode<W: Write, F>(w: &mut W, compression: Compression, f: F) -> Result<(), NbtError>
+where
+    F: FnOnce(&mut dyn Write) -> Result<(), NbtError>,
+{
+    match compression {
+        Compression::None => f(w),
+        Compression::Gzip(level) => {
+            let mut encoder = GzEncoder::new(w, flate2::Compression::new(level));
+            f(&mut encoder)?;
+            encoder.finish()?;
+            Ok(())
+        }
+        Compression::Zlib(level) => {
+            let mut encoder = ZlibEncoder::new(w, flate2::Compression::new(level));
+            f(&mut encoder)?;
+            encoder.finish()?;
+            Ok(())
+        }
+        Compression::Lz4 => {
+            let mut encoder = Lz4BlockEncoder::new(w);
+            f(&mut encoder)?;
+            encoder.finish()?;
+            Ok(())
+        }
+    }
+}
+
+// ---- LZ4 block streams --------------------------------------------------------------------------
+fn lz4_checksum(data: &[u8]) -> u32 {
+    XxHash32::oneshot(LZ4_CHECKSUM_SEED, data) & 0x0fff_ffff
+}
+
+fn invalid_lz4(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("lz4: {}", message))
+}
+
+pub(crate) struct Lz4BlockDecoder<R> {
+    inner: R,
+    block: Vec<u8>,
+    pos: usize,
+    finished: bool,
+}
+
+impl<R: Read> Lz4BlockDecoder<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Lz4BlockDecoder {
+            inner,
+            block: Vec::new(),
+            pos: 0,
+            finished: false,
+        }
+    }
+
+    fn next_block(&mut self) -> std::io::Result<()> {
+        let mut magic = [0u8; 8];
+        self.inner.read_exact(&mut magic)?;
+        if &magic != LZ4_MAGIC {
+            return Err(invalid_lz4("bad block magic"));
+        }
+        let method = self.inner.read_u8()? & 0xf0;
+        let compressed_len = self.inner.read_i32::<LittleEndian>()?;
+        let original_len = self.inner.read_i32::<LittleEndian>()?;
+        let checksum = self.inner.read_u32::<LittleEndian>()?;
+        if !(0..=LZ4_MAX_BLOCK_SIZE).contains(&original_len)
+            || !(0..=LZ4_MAX_BLOCK_SIZE + LZ4_MAX_BLOCK_SIZE / 255 + 16).contains(&compressed_len)
+        {
+            return Err(invalid_lz4("bad block length"));
+        }
+
+        let mut compressed = vec![0u8; compressed_len as usize];
+        self.inner.read_exact(&mut compressed)?;
+        if original_len == 0 {
+            self.finished = true;
+            self.block.clear();
+            self.pos = 0;
+            return Ok(());
+        }
+
+        self.block = match method {
+            LZ4_METHOD_RAW if compressed_len == original_len => compressed,
+            LZ4_METHOD_LZ4 => lz4_flex::block::decompress(&compressed, original_len as usize)
+                .map_err(|e| invalid_lz4(&e.to_string()))?,
+            _ => return Err(invalid_lz4("bad block method")),
+        };
+        if self.block.len() != original_len as usize {
+            return Err(invalid_lz4("bad block length"));
+        }
+        if lz4_checksum(&self.block) != checksum {
+            return Err(invalid_lz4("checksum mismatch"));
+        }
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Lz4BlockDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.block.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.next_block()?;
+        }
+        let n = buf.len().min(self.block.len() - self.pos);
+        buf[..n].copy_from_slice(&self.block[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+pub(crate) struct Lz4BlockEncoder<W: Write> {
+    inner: W,
+    block: Vec<u8>,
+}
+
+impl<W: Write> Lz4BlockEncoder<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Lz4BlockEncoder {
+            inner,
+            block: Vec::with_capacity(LZ4_BLOCK_SIZE),
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> std::io::Result<W> {
+        self.flush_block()?;
+        self.write_block(LZ4_METHOD_RAW, &[], 0, 0)?;
+        Ok(self.inner)
+    }
+
+    fn flush_block(&mut self) -> std::io::Result<()> {
+        if self.block.is_empty() {
+            return Ok(());
+        }
+        let checksum = lz4_checksum(&self.block);
+        let compressed = lz4_flex::block::compress(&self.block);
+        let block = std::mem::take(&mut self.block);
+        if compressed.len() < block.len() {
+            self.write_block(LZ4_METHOD_LZ4, &compressed, block.len(), checksum)?;
+        } else {
+            self.write_block(LZ4_METHOD_RAW, &block, block.len(), checksum)?;
+        }
+        self.block = block;
+        self.block.clear();
+        Ok(())
+    }
+
+    fn write_block(
+        &mut self,
+        method: u8,
+        data: &[u8],
+        original_len: usize,
+        checksum: u32,
+    ) -> std::io::Result<()> {
+        // lz4-java stores log2(block size) - 10 in the low nibble of the token
+        let level = (LZ4_BLOCK_SIZE.trailing_zeros() - 10) as u8;
+        self.inner.write_all(LZ4_MAGIC)?;
+        self.inner.write_u8(method | level)?;
+        self.inner.write_i32::<LittleEndian>(data.len() as i32)?;
+        self.inner.write_i32::<LittleEndian>(original_len as i32)?;
+        self.inner.write_u32::<LittleEndian>(checksum)?;
+        self.inner.write_all(data)
+    }
+}
+
+impl<W: Write> Write for Lz4BlockEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(LZ4_BLOCK_SIZE - self.block.len());
+        self.block.extend_from_slice(&buf[..n]);
+        if self.block.len() == LZ4_BLOCK_SIZE {
+            self.flush_block()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,312 @@
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, Deserialize, IntoDeserializer, Visitor};
+
+use crate::error::NbtError;
+use crate::field::NbtField;
+use crate::list::NbtList;
+use crate::value::NbtValue;
+
+/// Deserializes a value from an NBT tree, borrowing strings from it where possible.
+///
+/// `bool` accepts both `Boolean` and `Byte` values of 0 or 1, typed arrays and lists are
+/// interchangeable for any sequence type.
+pub fn from_value<'de, T: Deserialize<'de>>(field: &'de NbtField) -> Result<T, NbtError> {
+    T::deserialize(Node::from(&field.value))
+}
+
+/// A borrowed view of anything that can be deserialized, including the elements of
+/// primitive lists which are not stored as `NbtValue`s.
+#[derive(Clone, Copy)]
+pub(crate) enum Node<'de> {
+    Byte(u8),
+    Boolean(bool),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(&'de str),
+    List(&'de NbtList),
+    Compound(&'de [NbtField]),
+    ByteArray(&'de [u8]),
+    IntArray(&'de [i32]),
+    LongArray(&'de [i64]),
+    End,
+}
+
+impl<'de> From<&'de NbtValue> for Node<'de> {
+    fn from(value: &'de NbtValue) -> Self {
+        match value {
+            NbtValue::Byte(b) => Node::Byte(*b),
+            NbtValue::Boolean(b) => Node::Boolean(*b),
+            NbtValue::Short(s) => Node::Short(*s),
+            NbtValue::Int(i) => Node::Int(*i),
+            NbtValue::Long(l) => Node::Long(*l),
+            NbtValue::Float(f) => Node::Float(*f),
+            NbtValue::Double(d) => Node::Double(*d),
+            NbtValue::String(s) => Node::String(s),
+            NbtValue::List(l) => Node::List(l),
+            NbtValue::Compound(c) => Node::Compound(c),
+            NbtValue::ByteArray(arr) => Node::ByteArray(arr),
+            NbtValue::IntArray(arr) => Node::IntArray(arr),
+            NbtValue::LongArray(arr) => Node::LongArray(arr),
+            NbtValue::End => Node::End,
+        }
+    }
+}
+
+impl<'de> Node<'de> {
+    fn list_elements(list: &'de NbtList) -> Box<dyn Iterator<Item = Node<'de>> + 'de> {
+        match list {
+            NbtList::Byte(v) => Box::new(v.iter().map(|b| Node::Byte(*b))),
+            NbtList::Boolean(v) => Box::new(v.iter().map(|b| Node::Boolean(*b))),
+            NbtList::Short(v) => Box::new(v.iter().map(|s| Node::Short(*s))),
+            NbtList::Int(v) => Box::new(v.iter().map(|i| Node::Int(*i))),
+            NbtList::Long(v) => Box::new(v.iter().map(|l| Node::Long(*l))),
+            NbtList::Float(v) => Box::new(v.iter().map(|f| Node::Float(*f))),
+            NbtList::Double(v) => Box::new(v.iter().map(|d| Node::Double(*d))),
+            NbtList::String(v) => Box::new(v.iter().map(|s| Node::String(s))),
+            NbtList::List(v) => Box::new(v.iter().map(Node::from)),
+            NbtList::Compound(v) => Box::new(v.iter().map(|f| Node::from(&f.value))),
+            NbtList::ByteArray(v) => Box::new(v.iter().map(|arr| Node::ByteArray(arr))),
+            NbtList::IntArray(v) => Box::new(v.iter().map(|arr| Node::IntArray(arr))),
+            NbtList::LongArray(v) => Box::new(v.iter().map(|arr| Node::LongArray(arr))),
+            NbtList::End => Box::new(std::iter::empty()),
+        }
+    }
+
+    fn invalid_type<E: de::Error>(self, expected: &dyn de::Expected) -> E {
+        let unexpected = match self {
+            Node::Byte(b) => de::Unexpected::Signed(b as i8 as i64),
+            Node::Boolean(b) => de::Unexpected::Bool(b),
+            Node::Short(s) => de::Unexpected::Signed(s as i64),
+            Node::Int(i) => de::Unexpected::Signed(i as i64),
+            Node::Long(l) => de::Unexpected::Signed(l),
+            Node::Float(f) => de::Unexpected::Float(f as f64),
+            Node::Double(d) => de::Unexpected::Float(d),
+            Node::String(s) => de::Unexpected::Str(s),
+            Node::List(_) | Node::ByteArray(_) | Node::IntArray(_) | Node::LongArray(_) => {
+                de::Unexpected::Seq
+            }
+            Node::Compound(_) => de::Unexpected::Map,
+            Node::End => de::Unexpected::Unit,
+        };
+        de::Error::invalid_type(unexpected, expected)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Node<'de> {
+    type Error = NbtError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+        match self {
+            Node::Byte(b) => visitor.visit_u8(b),
+            Node::Boolean(b) => visitor.visit_bool(b),
+            Node::Short(s) => visitor.visit_i16(s),
+            Node::Int(i) => visitor.visit_i32(i),
+            Node::Long(l) => visitor.visit_i64(l),
+            Node::Float(f) => visitor.visit_f32(f),
+            Node::Double(d) => visitor.visit_f64(d),
+            Node::String(s) => visitor.visit_borrowed_str(s),
+            Node::List(l) => visitor.visit_seq(SeqAccess(Node::list_elements(l))),
+            Node::Compound(fields) => visitor.visit_map(CompoundAccess {
+                fields: fields.iter(),
+                value: None,
+            }),
+            Node::ByteArray(arr) => {
+                visitor.visit_seq(SeqAccess(Box::new(arr.iter().map(|b| Node::Byte(*b)))))
+            }
+            Node::IntArray(arr) => {
+                visitor.visit_seq(SeqAccess(Box::new(arr.iter().map(|i| Node::Int(*i)))))
+            }
+            Node::LongArray(arr) => {
+                visitor.visit_seq(SeqAccess(Box::new(arr.iter().map(|l| Node::Long(*l)))))
+            }
+            Node::End => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+        match self {
+            Node::Boolean(b) => visitor.visit_bool(b),
+            Node::Byte(0) => visitor.visit_bool(false),
+            Node::Byte(1) => visitor.visit_bool(true),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+        match self {
+            Node::Byte(b) => visitor.visit_i8(b as i8),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+        match self {
+            Node::ByteArray(arr) => visitor.visit_borrowed_bytes(arr),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+        match self {
+            Node::End => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+        match self {
+            Node::End => visitor.visit_unit(),
+            Node::Compound([]) => visitor.visit_unit(),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, NbtError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, NbtError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, NbtError> {
+        match self {
+            Node::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Node::Compound([field]) => visitor.visit_enum(EnumAccess {
+                variant: &field.name,
+                value: Node::from(&field.value),
+            }),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+// ---- Access types -------------------------------------------------------------------------------
+struct SeqAccess<'de>(Box<dyn Iterator<Item = Node<'de>> + 'de>);
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de> {
+    type Error = NbtError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, NbtError> {
+        self.0.next().map(|node| seed.deserialize(node)).transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.0.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper),
+            _ => None,
+        }
+    }
+}
+
+struct CompoundAccess<'de> {
+    fields: std::slice::Iter<'de, NbtField>,
+    value: Option<&'de NbtValue>,
+}
+
+impl<'de> de::MapAccess<'de> for CompoundAccess<'de> {
+    type Error = NbtError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, NbtError> {
+        match self.fields.next() {
+            Some(field) => {
+                self.value = Some(&field.value);
+                seed.deserialize(BorrowedStrDeserializer::new(&field.name))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, NbtError> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| NbtError::SerdeError("next_value called before next_key".to_string()))?;
+        seed.deserialize(Node::from(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+/// Externally tagged enum variants stored as a single-entry compound `{variant: value}`.
+struct EnumAccess<'de> {
+    variant: &'de str,
+    value: Node<'de>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
+    type Error = NbtError;
+    type Variant = Node<'de>;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Node<'de>), NbtError> {
+        let variant = seed.deserialize(BorrowedStrDeserializer::<NbtError>::new(self.variant))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Node<'de> {
+    type Error = NbtError;
+
+    fn unit_variant(self) -> Result<(), NbtError> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, NbtError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, NbtError> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, NbtError> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
@@ -0,0 +1,502 @@
+use std::io::Read;
+
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+
+use crate::compound::NbtCompound;
+use crate::encode::Encoding;
+use crate::error::NbtError;
+use crate::field::NbtField;
+use crate::list::NbtList;
+use crate::mutf8;
+use crate::value::*;
+
+/// Upper bound for preallocating list and array buffers, so that a corrupt length cannot
+/// allocate more memory than the input actually contains.
+const MAX_PREALLOC: usize = 4096;
+
+/// Limits applied while reading NBT from untrusted sources.
+///
+/// The defaults match vanilla: compounds and lists may be nested 512 levels deep, allocation
+/// and input size are unlimited and strings must be valid modified UTF-8. Unlike vanilla, a name
+/// that occurs twice in a compound is rejected, so that no field is dropped unnoticed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// Maximum number of nested compounds and lists, counting the root.
+    pub max_depth: usize,
+    /// Maximum number of bytes allocated for names, strings, arrays, list elements and compound
+    /// fields, including the name index of compounds.
+    pub max_alloc: Option<usize>,
+    /// Maximum number of (decompressed) bytes read from the input.
+    pub max_size: Option<u64>,
+    /// Accept strings that mix modified and standard UTF-8, replacing invalid sequences with
+    /// U+FFFD instead of failing.
+    pub lenient_strings: bool,
+    /// How the input is encoded, big-endian for Java and little-endian for Bedrock.
+    pub encoding: Encoding,
+    /// What to do with a name that occurs twice in a compound.
+    pub duplicate_keys: DuplicateKeys,
+    /// Reject roots that are not compounds with `NbtError::RootNotCompoundError`, as the classic
+    /// file format requires. By default a root of any type is accepted.
+    pub require_compound_root: bool,
+}
+
+/// How a compound that contains the same name twice is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Fail with `NbtError::DuplicateKey`. The default.
+    Reject,
+    /// Keep the first value and skip the later ones.
+    KeepFirst,
+    /// Replace the value in place with the later one, like vanilla does.
+    KeepLast,
+}
+
+/// The limit from [`ReadOptions`] that was exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadLimit {
+    Depth(usize),
+    Alloc(usize),
+    Size(u64),
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions {
+            max_depth: 512,
+            max_alloc: None,
+            max_size: None,
+            lenient_strings: false,
+            encoding: Encoding::BigEndian,
+            duplicate_keys: DuplicateKeys::Reject,
+            require_compound_root: false,
+        }
+    }
+}
+
+impl ReadOptions {
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn max_alloc(mut self, max_alloc: usize) -> Self {
+        self.max_alloc = Some(max_alloc);
+        self
+    }
+
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    pub fn lenient_strings(mut self, lenient_strings: bool) -> Self {
+        self.lenient_strings = lenient_strings;
+        self
+    }
+
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    pub fn require_compound_root(mut self, require_compound_root: bool) -> Self {
+        self.require_compound_root = require_compound_root;
+        self
+    }
+}
+
+// ---- Size limit ---------------------------------------------------------------------------------
+/// Counts the bytes read and stops at `max`, remembering whether more were requested.
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    read: u64,
+    max: Option<u64>,
+    exceeded: bool,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let buf = match self.max {
+            Some(max) => {
+                let remaining = (max - self.read).min(buf.len() as u64) as usize;
+                if remaining == 0 && !buf.is_empty() {
+                    self.exceeded = true;
+                }
+                &mut buf[..remaining]
+            }
+            None => buf,
+        };
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        Ok(n)
+    }
+}
+
+// ---- Decoder ------------------------------------------------------------------------------------
+/// Reads NBT payloads while enforcing [`ReadOptions`].
+pub(crate) struct Decoder<R> {
+    reader: CountingReader<R>,
+    max_depth: usize,
+    max_alloc: Option<usize>,
+    lenient_strings: bool,
+    encoding: Encoding,
+    duplicate_keys: DuplicateKeys,
+    require_compound_root: bool,
+    depth: usize,
+    allocated: usize,
+}
+
+impl<R: Read> Decoder<R> {
+    pub(crate) fn new(reader: R, options: &ReadOptions) -> Self {
+        Decoder {
+            reader: CountingReader {
+                inner: reader,
+                read: 0,
+                max: options.max_size,
+                exceeded: false,
+            },
+            max_depth: options.max_depth,
+            max_alloc: options.max_alloc,
+            lenient_strings: options.lenient_strings,
+            encoding: options.encoding,
+            duplicate_keys: options.duplicate_keys,
+            require_compound_root: options.require_compound_root,
+            depth: 0,
+            allocated: 0,
+        }
+    }
+
+    pub(crate) fn reader(&mut self) -> &mut impl Read {
+        &mut self.reader
+    }
+
+    pub(crate) fn into_inner(self) -> R {
+        self.reader.inner
+    }
+
+    /// Adds the current offset and `tag` to an error, replacing the end-of-data error caused by
+    /// the size limit with the limit error.
+    pub(crate) fn locate(&self, e: NbtError, tag: u8) -> NbtError {
+        let e = match (e, self.reader.max) {
+            (NbtError::UnexpectedEof, Some(max)) if self.reader.exceeded => {
+                NbtError::LimitExceeded(ReadLimit::Size(max))
+            }
+            (e, _) => e,
+        };
+        e.at(self.reader.read, tag)
+    }
+
+    /// Reads a complete root field. The root name is not part of error paths.
+    pub(crate) fn read_root(&mut self) -> Result<NbtField, NbtError> {
+        let (tag, name) = match self.read_header() {
+            Ok(Some(header)) => header,
+            Ok(None) => (TAG_END, String::new()),
+            Err(e) => return Err(self.locate(e, TAG_END)),
+        };
+        self.check_root(tag)?;
+        let value = match tag {
+            TAG_END => NbtValue::End,
+            tag => self.read_payload(tag)?,
+        };
+        Ok(NbtField { name, value })
+    }
+
+    /// Reads a root without a name, as sent by the Java protocol since 1.20.2.
+    pub(crate) fn read_unnamed_root(&mut self) -> Result<NbtField, NbtError> {
+        let tag = self
+            .reader
+            .read_u8()
+            .map_err(|e| self.locate(e.into(), TAG_END))?;
+        if tag > TAG_LONG_ARRAY {
+            return Err(self.locate(NbtError::UnknownTag(tag), TAG_END));
+        }
+        self.check_root(tag)?;
+        let value = match tag {
+            TAG_END => NbtValue::End,
+            tag => self.read_payload(tag)?,
+        };
+        Ok(NbtField {
+            name: String::new(),
+            value,
+        })
+    }
+
+    /// Fails for a root that is not a compound if the options require one.
+    pub(crate) fn check_root(&self, tag: u8) -> Result<(), NbtError> {
+        if self.require_compound_root && tag != TAG_COMPOUND {
+            return Err(NbtError::RootNotCompoundError);
+        }
+        Ok(())
+    }
+
+    /// Reads the tag and name of a field, or `None` for `TAG_End`.
+    pub(crate) fn read_header(&mut self) -> Result<Option<(u8, String)>, NbtError> {
+        let tag = self.reader.read_u8()?;
+        if tag == TAG_END {
+            return Ok(None);
+        }
+        if tag > TAG_LONG_ARRAY {
+            return Err(NbtError::UnknownTag(tag));
+        }
+        Ok(Some((tag, self.read_string()?)))
+    }
+
+    /// Reads the payload of a `tag`, adding position context to any error.
+    pub(crate) fn read_payload(&mut self, tag: u8) -> Result<NbtValue, NbtError> {
+        self.payload(tag).map_err(|e| self.locate(e, tag))
+    }
+
+    fn payload(&mut self, tag: u8) -> Result<NbtValue, NbtError> {
+        match tag {
+            TAG_LIST => self.read_list(),
+            TAG_COMPOUND => self.read_compound(),
+            _ => self.read_leaf(tag),
+        }
+    }
+
+    fn read_leaf(&mut self, tag: u8) -> Result<NbtValue, NbtError> {
+        Ok(match tag {
+            TAG_BYTE => NbtValue::Byte(self.reader.read_u8()?),
+            TAG_SHORT => NbtValue::Short(self.read_i16()?),
+            TAG_INT => NbtValue::Int(self.read_i32()?),
+            TAG_LONG => NbtValue::Long(self.read_i64()?),
+            TAG_FLOAT => NbtValue::Float(self.read_f32()?),
+            TAG_DOUBLE => NbtValue::Double(self.read_f64()?),
+            TAG_BYTE_ARRAY => NbtValue::ByteArray(self.read_byte_array()?),
+            TAG_STRING => NbtValue::String(self.read_string()?),
+            TAG_INT_ARRAY => NbtValue::IntArray(self.read_int_array()?),
+            TAG_LONG_ARRAY => NbtValue::LongArray(self.read_long_array()?),
+            _ => return Err(NbtError::UnknownTag(tag)),
+        })
+    }
+
+    /// Reads a string, as modified UTF-8 for Java and UTF-8 for Bedrock.
+    pub(crate) fn read_string(&mut self) -> Result<String, NbtError> {
+        let len = self.read_string_len()?;
+        let buf = self.read_bytes(len)?;
+        match self.encoding {
+            Encoding::BigEndian => mutf8::into_string(buf, self.lenient_strings),
+            _ if self.lenient_strings => Ok(String::from_utf8_lossy(&buf).into_owned()),
+            _ => Ok(String::from_utf8(buf)?),
+        }
+    }
+
+    pub(crate) fn read_string_len(&mut self) -> Result<usize, NbtError> {
+        Ok(match self.encoding {
+            Encoding::BigEndian => self.reader.read_u16::<BigEndian>()? as usize,
+            Encoding::LittleEndian => self.reader.read_u16::<LittleEndian>()? as usize,
+            Encoding::NetworkLittleEndian => self.read_varint(5)? as u32 as usize,
+        })
+    }
+
+    /// Reads an `i32` length prefix, rejecting negative values.
+    pub(crate) fn read_len(&mut self) -> Result<usize, NbtError> {
+        let len = self.read_i32()?;
+        usize::try_from(len).map_err(|_| NbtError::NegativeLength(len))
+    }
+
+    pub(crate) fn read_byte_array(&mut self) -> Result<Vec<u8>, NbtError> {
+        let len = self.read_len()?;
+        self.read_bytes(len)
+    }
+
+    /// Reads `len` bytes, growing the buffer as data arrives so that a bogus length cannot
+    /// allocate more than the input holds.
+    fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>, NbtError> {
+        self.charge(len)?;
+        let mut buf = Vec::with_capacity(len.min(MAX_PREALLOC));
+        (&mut self.reader).take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() != len {
+            return Err(NbtError::UnexpectedEof);
+        }
+        Ok(buf)
+    }
+
+    pub(crate) fn read_int_array(&mut self) -> Result<Vec<i32>, NbtError> {
+        let len = self.read_len()?;
+        self.read_elements(len, TAG_INT_ARRAY, Self::read_i32)
+    }
+
+    pub(crate) fn read_long_array(&mut self) -> Result<Vec<i64>, NbtError> {
+        let len = self.read_len()?;
+        self.read_elements(len, TAG_LONG_ARRAY, Self::read_i64)
+    }
+
+    // `read_compound` and `read_list` recurse, so everything else is kept out of them to keep
+    // their stack frames small.
+    fn read_compound(&mut self) -> Result<NbtValue, NbtError> {
+        self.enter()?;
+        let mut fields = NbtCompound::new();
+        while let Some((tag, name)) = self.read_header()? {
+            let value = self.read_payload(tag).map_err(|e| e.in_field(&name))?;
+            self.add_field(&mut fields, NbtField { name, value })?;
+        }
+        self.leave();
+        Ok(NbtValue::Compound(fields))
+    }
+
+    /// Adds a field read by `read_compound`, applying the duplicate key policy.
+    fn add_field(&mut self, fields: &mut NbtCompound, field: NbtField) -> Result<(), NbtError> {
+        self.charge(std::mem::size_of::<NbtField>())?;
+        match self.duplicate_keys {
+            _ if !fields.contains_key(&field.name) => {
+                self.charge(NbtCompound::index_cost(&field.name))?
+            }
+            DuplicateKeys::Reject => return Err(NbtError::DuplicateKey(field.name)),
+            DuplicateKeys::KeepFirst => return Ok(()),
+            DuplicateKeys::KeepLast => {}
+        }
+        fields.insert(field);
+        Ok(())
+    }
+
+    fn read_list(&mut self) -> Result<NbtValue, NbtError> {
+        self.enter()?;
+        let tag = self.reader.read_u8()?;
+        let len = self.read_len()?;
+        let list = match tag {
+            TAG_LIST => NbtList::List(self.read_elements(len, tag, Self::read_list)?),
+            TAG_COMPOUND => NbtList::Compound(self.read_elements(len, tag, |d| {
+                Ok(NbtField {
+                    name: String::new(),
+                    value: d.read_compound()?,
+                })
+            })?),
+            _ => self.read_flat_list(tag, len)?,
+        };
+        self.leave();
+        Ok(NbtValue::List(list))
+    }
+
+    fn read_flat_list(&mut self, tag: u8, len: usize) -> Result<NbtList, NbtError> {
+        Ok(match tag {
+            TAG_BYTE => NbtList::Byte(self.read_elements(len, tag, |d| Ok(d.reader.read_u8()?))?),
+            TAG_SHORT => NbtList::Short(self.read_elements(len, tag, Self::read_i16)?),
+            TAG_INT => NbtList::Int(self.read_elements(len, tag, Self::read_i32)?),
+            TAG_LONG => NbtList::Long(self.read_elements(len, tag, Self::read_i64)?),
+            TAG_FLOAT => NbtList::Float(self.read_elements(len, tag, Self::read_f32)?),
+            TAG_DOUBLE => NbtList::Double(self.read_elements(len, tag, Self::read_f64)?),
+            TAG_STRING => NbtList::String(self.read_elements(len, tag, Self::read_string)?),
+            TAG_BYTE_ARRAY => {
+                NbtList::ByteArray(self.read_elements(len, tag, Self::read_byte_array)?)
+            }
+            TAG_LONG_ARRAY => {
+                NbtList::LongArray(self.read_elements(len, tag, Self::read_long_array)?)
+            }
+            TAG_INT_ARRAY => {
+                NbtList::IntArray(self.read_elements(len, tag, Self::read_int_array)?)
+            }
+            TAG_END if len == 0 => NbtList::End,
+            _ => return Err(NbtError::InvalidListType(tag)),
+        })
+    }
+
+    fn read_elements<T>(
+        &mut self,
+        len: usize,
+        tag: u8,
+        mut read: impl FnMut(&mut Self) -> Result<T, NbtError>,
+    ) -> Result<Vec<T>, NbtError> {
+        self.charge(len.saturating_mul(std::mem::size_of::<T>()))?;
+        let mut list = Vec::with_capacity(len.min(MAX_PREALLOC));
+        for i in 0..len {
+            list.push(read(self).map_err(|e| self.locate(e, tag).in_element(i))?);
+        }
+        Ok(list)
+    }
+
+    // ---- Numbers --------------------------------------------------------------------------------
+    fn read_i16(&mut self) -> Result<i16, NbtError> {
+        Ok(match self.encoding {
+            Encoding::BigEndian => self.reader.read_i16::<BigEndian>()?,
+            Encoding::LittleEndian | Encoding::NetworkLittleEndian => {
+                self.reader.read_i16::<LittleEndian>()?
+            }
+        })
+    }
+
+    pub(crate) fn read_i32(&mut self) -> Result<i32, NbtError> {
+        Ok(match self.encoding {
+            Encoding::BigEndian => self.reader.read_i32::<BigEndian>()?,
+            Encoding::LittleEndian => self.reader.read_i32::<LittleEndian>()?,
+            Encoding::NetworkLittleEndian => {
+                let v = self.read_varint(5)? as u32;
+                (v >> 1) as i32 ^ -((v & 1) as i32)
+            }
+        })
+    }
+
+    pub(crate) fn read_i64(&mut self) -> Result<i64, NbtError> {
+        Ok(match self.encoding {
+            Encoding::BigEndian => self.reader.read_i64::<BigEndian>()?,
+            Encoding::LittleEndian => self.reader.read_i64::<LittleEndian>()?,
+            Encoding::NetworkLittleEndian => {
+                let v = self.read_varint(10)?;
+                (v >> 1) as i64 ^ -((v & 1) as i64)
+            }
+        })
+    }
+
+    fn read_f32(&mut self) -> Result<f32, NbtError> {
+        Ok(match self.encoding {
+            Encoding::BigEndian => self.reader.read_f32::<BigEndian>()?,
+            Encoding::LittleEndian | Encoding::NetworkLittleEndian => {
+                self.reader.read_f32::<LittleEndian>()?
+            }
+        })
+    }
+
+    fn read_f64(&mut self) -> Result<f64, NbtError> {
+        Ok(match self.encoding {
+            Encoding::BigEndian => self.reader.read_f64::<BigEndian>()?,
+            Encoding::LittleEndian | Encoding::NetworkLittleEndian => {
+                self.reader.read_f64::<LittleEndian>()?
+            }
+        })
+    }
+
+    /// Reads an unsigned LEB128 VarInt of at most `max_bytes` bytes.
+    fn read_varint(&mut self, max_bytes: u32) -> Result<u64, NbtError> {
+        let mut value = 0;
+        for i in 0..max_bytes {
+            let b = self.reader.read_u8()?;
+            value |= ((b & 0x7f) as u64) << (7 * i);
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(NbtError::InvalidVarInt)
+    }
+
+    pub(crate) fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    // ---- Accounting -----------------------------------------------------------------------------
+    pub(crate) fn enter(&mut self) -> Result<(), NbtError> {
+        if self.depth >= self.max_depth {
+            return Err(NbtError::LimitExceeded(ReadLimit::Depth(self.max_depth)));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    pub(crate) fn charge(&mut self, bytes: usize) -> Result<(), NbtError> {
+        self.allocated = self.allocated.saturating_add(bytes);
+        match self.max_alloc {
+            Some(max) if self.allocated > max => {
+                Err(NbtError::LimitExceeded(ReadLimit::Alloc(max)))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+
+use crate::error::{push_field, push_index};
+use crate::field::NbtField;
+use crate::path::elements;
+use crate::value::*;
+
+/// Values longer than this are shortened by [`render_diff`].
+const MAX_RENDERED_LEN: usize = 80;
+
+/// A difference between two NBT trees, see [`diff`]. Paths have the same form as in errors, e.g.
+/// `Level.Sections[3].Y`, and are empty for the root.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtChange {
+    /// A field or element that only exists in the new tree.
+    Added { path: String, value: NbtValue },
+    /// A field or element that only exists in the old tree.
+    Removed { path: String, value: NbtValue },
+    /// A value whose tag, or for lists whose element tag, differs.
+    TypeChanged {
+        path: String,
+        old: NbtValue,
+        new: NbtValue,
+    },
+    /// A number or string with a different value.
+    ValueChanged {
+        path: String,
+        old: NbtValue,
+        new: NbtValue,
+    },
+    /// A list or array with a different number of elements. The extra elements are reported as
+    /// added or removed.
+    LengthChanged {
+        path: String,
+        old: usize,
+        new: usize,
+    },
+}
+
+impl NbtChange {
+    pub fn path(&self) -> &str {
+        match self {
+            NbtChange::Added { path, .. }
+            | NbtChange::Removed { path, .. }
+            | NbtChange::TypeChanged { path, .. }
+            | NbtChange::ValueChanged { path, .. }
+            | NbtChange::LengthChanged { path, .. } => path,
+        }
+    }
+}
+
+// ---- Diff ---------------------------------------------------------------------------------------
+
+/// Compares two trees and lists every change from `old` to `new`, in document order.
+///
+/// Compound fields are matched by name, so reordered fields are not a change, and list and array
+/// elements by index. The names of the roots are not compared.
+pub fn diff(old: &NbtField, new: &NbtField) -> Vec<NbtChange> {
+    let mut differ = Differ {
+        path: String::new(),
+        changes: Vec::new(),
+    };
+    differ.value(&old.value, &new.value);
+    differ.changes
+}
+
+struct Differ {
+    path: String,
+    changes: Vec<NbtChange>,
+}
+
+impl Differ {
+    fn value(&mut self, old: &NbtValue, new: &NbtValue) {
+        match (old, new) {
+            (NbtValue::Compound(old), NbtValue::Compound(new)) => {
+                for field in old.iter() {
+                    self.at_field(&field.name, |d| match new.get(&field.name) {
+                        Some(other) => d.value(&field.value, &other.value),
+                        None => d.changes.push(NbtChange::Removed {
+                            path: d.path.clone(),
+                            value: field.value.clone(),
+                        }),
+                    });
+                }
+                for field in new.iter().filter(|f| !old.contains_key(&f.name)) {
+                    self.at_field(&field.name, |d| {
+                        d.changes.push(NbtChange::Added {
+                            path: d.path.clone(),
+                            value: field.value.clone(),
+                        })
+                    });
+                }
+            }
+            (NbtValue::List(_), NbtValue::List(_)) if element_tags_differ(old, new) => {
+                self.type_changed(old, new)
+            }
+            (NbtValue::List(_), NbtValue::List(_))
+            | (NbtValue::ByteArray(_), NbtValue::ByteArray(_))
+            | (NbtValue::IntArray(_), NbtValue::IntArray(_))
+            | (NbtValue::LongArray(_), NbtValue::LongArray(_)) => self.elements(old, new),
+            _ if old.tag() != new.tag() => self.type_changed(old, new),
+            _ if old != new => self.changes.push(NbtChange::ValueChanged {
+                path: self.path.clone(),
+                old: old.clone(),
+                new: new.clone(),
+            }),
+            _ => {}
+        }
+    }
+
+    fn elements(&mut self, old: &NbtValue, new: &NbtValue) {
+        let (old, new) = (elements(old), elements(new));
+        let (old_len, new_len) = (old.len(), new.len());
+        if old_len != new_len {
+            self.changes.push(NbtChange::LengthChanged {
+                path: self.path.clone(),
+                old: old_len,
+                new: new_len,
+            });
+        }
+        let mut old = old.fuse();
+        let mut new = new.fuse();
+        for i in 0..old_len.max(new_len) {
+            let len = self.path.len();
+            push_index(&mut self.path, i);
+            match (old.next(), new.next()) {
+                (Some(old), Some(new)) => self.value(&old, &new),
+                (Some(old), None) => self.changes.push(NbtChange::Removed {
+                    path: self.path.clone(),
+                    value: old.into_owned(),
+                }),
+                (None, Some(new)) => self.changes.push(NbtChange::Added {
+                    path: self.path.clone(),
+                    value: new.into_owned(),
+                }),
+                (None, None) => {}
+            }
+            self.path.truncate(len);
+        }
+    }
+
+    fn type_changed(&mut self, old: &NbtValue, new: &NbtValue) {
+        self.changes.push(NbtChange::TypeChanged {
+            path: self.path.clone(),
+            old: old.clone(),
+            new: new.clone(),
+        });
+    }
+
+    fn at_field(&mut self, name: &str, diff: impl FnOnce(&mut Self)) {
+        let len = self.path.len();
+        push_field(&mut self.path, name);
+        diff(self);
+        self.path.truncate(len);
+    }
+}
+
+/// Whether two lists hold elements of different tags. An empty list fits any tag.
+fn element_tags_differ(old: &NbtValue, new: &NbtValue) -> bool {
+    let first_tag = |list| elements(list).next().map(|e| e.tag());
+    matches!((first_tag(old), first_tag(new)), (Some(a), Some(b)) if a != b)
+}
+
+// ---- Rendering ----------------------------------------------------------------------------------
+
+/// Renders changes one per line, marking additions with `+`, removals with `-`, changed values
+/// and types with `~` and changed lengths with `#`. Long values are shortened.
+pub fn render_diff(changes: &[NbtChange]) -> String {
+    changes.iter().map(|c| format!("{}\n", c)).collect()
+}
+
+impl fmt::Display for NbtChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = match self.path() {
+            "" => "(root)",
+            path => path,
+        };
+        match self {
+            NbtChange::Added { value, .. } => write!(f, "+ {}: {}", path, Short(value)),
+            NbtChange::Removed { value, .. } => write!(f, "- {}: {}", path, Short(value)),
+            NbtChange::TypeChanged { old, new, .. } => write!(
+                f,
+                "~ {}: {} -> {} (tag {} -> {})",
+                path,
+                Short(old),
+                Short(new),
+                old.tag(),
+                new.tag()
+            ),
+            NbtChange::ValueChanged { old, new, .. } => {
+                write!(f, "~ {}: {} -> {}", path, Short(old), Short(new))
+            }
+            NbtChange::LengthChanged { old, new, .. } => {
+                write!(f, "# {}: {} -> {} elements", path, old, new)
+            }
+        }
+    }
+}
+
+/// SNBT of a value, shortened to [`MAX_RENDERED_LEN`] characters.
+struct Short<'a>(&'a NbtValue);
+
+impl fmt::Display for Short<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let snbt = self.0.to_snbt();
+        match snbt.char_indices().nth(MAX_RENDERED_LEN) {
+            Some((end, _)) => write!(f, "{}...", &snbt[..end]),
+            None => f.write_str(&snbt),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::io::Write;
+
+use byteorder::{BigEndian, LittleEndian, WriteBytesExt};
+
+use crate::mutf8;
+
+/// How numbers and length prefixes are laid out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// Java Edition files and protocol.
+    #[default]
+    BigEndian,
+    /// Bedrock Edition files, such as level.dat and .mcstructure.
+    LittleEndian,
+    /// Bedrock Edition protocol: little-endian, with zigzag VarInts for ints, longs and list and
+    /// array lengths, and unsigned VarInts for string lengths.
+    NetworkLittleEndian,
+}
+
+/// Options for writing NBT.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    pub encoding: Encoding,
+}
+
+impl Encoding {
+    /// The number of bytes of an encoded string, without the length prefix.
+    pub(crate) fn string_len(&self, s: &str) -> usize {
+        match self {
+            Encoding::BigEndian => mutf8::encoded_len(s),
+            Encoding::LittleEndian | Encoding::NetworkLittleEndian => s.len(),
+        }
+    }
+}
+
+impl WriteOptions {
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+}
+
+// ---- Encoder ------------------------------------------------------------------------------------
+/// Writes NBT numbers, strings and lengths in an [`Encoding`].
+pub struct Encoder<W> {
+    writer: W,
+    encoding: Encoding,
+}
+
+impl<W: Write> Encoder<W> {
+    pub(crate) fn new(writer: W, encoding: Encoding) -> Self {
+        Encoder { writer, encoding }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.writer
+    }
+
+    pub(crate) fn write_u8(&mut self, v: u8) -> std::io::Result<()> {
+        self.writer.write_u8(v)
+    }
+
+    pub(crate) fn write_i16(&mut self, v: i16) -> std::io::Result<()> {
+        match self.encoding {
+            Encoding::BigEndian => self.writer.write_i16::<BigEndian>(v),
+            Encoding::LittleEndian | Encoding::NetworkLittleEndian => {
+                self.writer.write_i16::<LittleEndian>(v)
+            }
+        }
+    }
+
+    pub(crate) fn write_i32(&mut self, v: i32) -> std::io::Result<()> {
+        match self.encoding {
+            Encoding::BigEndian => self.writer.write_i32::<BigEndian>(v),
+            Encoding::LittleEndian => self.writer.write_i32::<LittleEndian>(v),
+            Encoding::NetworkLittleEndian => {
+                self.write_varint(((v << 1) ^ (v >> 31)) as u32 as u64)
+            }
+        }
+    }
+
+    pub(crate) fn write_i64(&mut self, v: i64) -> std::io::Result<()> {
+        match self.encoding {
+            Encoding::BigEndian => self.writer.write_i64::<BigEndian>(v),
+            Encoding::LittleEndian => self.writer.write_i64::<LittleEndian>(v),
+            Encoding::NetworkLittleEndian => self.write_varint(((v << 1) ^ (v >> 63)) as u64),
+        }
+    }
+
+    pub(crate) fn write_f32(&mut self, v: f32) -> std::io::Result<()> {
+        match self.encoding {
+            Encoding::BigEndian => self.writer.write_f32::<BigEndian>(v),
+            Encoding::LittleEndian | Encoding::NetworkLittleEndian => {
+                self.writer.write_f32::<LittleEndian>(v)
+            }
+        }
+    }
+
+    pub(crate) fn write_f64(&mut self, v: f64) -> std::io::Result<()> {
+        match self.encoding {
+            Encoding::BigEndian => self.writer.write_f64::<BigEndian>(v),
+            Encoding::LittleEndian | Encoding::NetworkLittleEndian => {
+                self.writer.write_f64::<LittleEndian>(v)
+            }
+        }
+    }
+
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.writer.write_all(bytes)
+    }
+
+    /// Writes the length prefix of a list or array, which must have been checked to fit.
+    pub(crate) fn write_len(&mut self, len: usize) -> std::io::Result<()> {
+        self.write_i32(len as i32)
+    }
+
+    /// Writes a length-prefixed string, as modified UTF-8 for Java and UTF-8 for Bedrock.
+    pub(crate) fn write_string(&mut self, s: &str) -> std::io::Result<()> {
+        let bytes = match self.encoding {
+            Encoding::BigEndian => mutf8::encode(s),
+            Encoding::LittleEndian | Encoding::NetworkLittleEndian => s.as_bytes().into(),
+        };
+        match self.encoding {
+            Encoding::BigEndian => self.writer.write_u16::<BigEndian>(bytes.len() as u16)?,
+            Encoding::LittleEndian => self.writer.write_u16::<LittleEndian>(bytes.len() as u16)?,
+            Encoding::NetworkLittleEndian => self.write_varint(bytes.len() as u64)?,
+        }
+        self.writer.write_all(&bytes)
+    }
+
+    /// Writes an unsigned LEB128 VarInt.
+    fn write_varint(&mut self, mut v: u64) -> std::io::Result<()> {
+        while v >= 0x80 {
+            self.writer.write_u8(v as u8 | 0x80)?;
+            v >>= 7;
+        }
+        self.writer.write_u8(v as u8)
+    }
+}
@@ -0,0 +1,59 @@
+use crate::compound::NbtCompound;
+use crate::field::NbtField;
+use crate::value::NbtValue;
+
+/// A field of a compound that may or may not exist yet, see [`NbtCompound::entry`].
+pub struct Entry<'a> {
+    compound: &'a mut NbtCompound,
+    name: String,
+    index: Option<usize>,
+}
+
+impl<'a> Entry<'a> {
+    pub(crate) fn new(compound: &'a mut NbtCompound, name: &str) -> Self {
+        let index = compound.index_of(name);
+        Entry {
+            compound,
+            name: name.to_string(),
+            index,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_occupied(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// Returns the value, appending a field with `value` if it does not exist.
+    pub fn or_insert(self, value: NbtValue) -> &'a mut NbtValue {
+        self.or_insert_with(|| value)
+    }
+
+    /// Returns the value, appending a field with the result of `default` if it does not exist.
+    pub fn or_insert_with<F: FnOnce() -> NbtValue>(self, default: F) -> &'a mut NbtValue {
+        let Entry {
+            compound,
+            name,
+            index,
+        } = self;
+        let index = index.unwrap_or_else(|| {
+            compound.insert(NbtField {
+                name,
+                value: default(),
+            });
+            compound.len() - 1
+        });
+        compound.value_at(index)
+    }
+
+    /// Calls `f` on the value if the field exists.
+    pub fn and_modify<F: FnOnce(&mut NbtValue)>(self, f: F) -> Self {
+        if let Some(index) = self.index {
+            f(self.compound.value_at(index));
+        }
+        self
+    }
+}
@@ -1,15 +1,248 @@
 
 
+use crate::decode::ReadLimit;
+
 #[derive(Debug)]
 pub enum NbtError {
     RootNotCompoundError,
     IOError(std::io::Error),
     Utf8Error(std::string::FromUtf8Error),
+    UnknownChunkCompression(u8),
+    CorruptChunk(String),
+    ChunkTooLarge(usize),
+    ExternalChunkUnavailable(i32, i32),
+    SnbtParseError { position: usize, message: String },
+    SerdeError(String),
+    UnknownTag(u8),
+    NegativeLength(i32),
+    LimitExceeded(ReadLimit),
+    InvalidListType(u8),
+    UnexpectedEof,
+    InvalidWrite(String),
+    /// A string that is not valid Java modified UTF-8, with the index of the offending byte.
+    Mutf8Error(usize),
+    /// A name or string at `path` whose encoding is longer than 65535 bytes.
+    StringTooLong { path: String, len: usize },
+    /// An array or list at `path` with more than `i32::MAX` elements.
+    ArrayTooLong { path: String, len: usize },
+    /// An element of a list of lists or compounds that has a different type.
+    MixedList { path: String, expected: u8, found: u8 },
+    /// A `TAG_End` value at `path`, which would close the enclosing compound early. Only an empty
+    /// root may be `TAG_End`.
+    MisplacedEnd { path: String },
+    /// A VarInt of the Bedrock network encoding that does not end within its maximum length.
+    InvalidVarInt,
+    /// A name that occurs twice in a compound, see `ReadOptions::duplicate_keys`.
+    DuplicateKey(String),
+    /// An NBT path that cannot be parsed, with the byte position of the problem.
+    InvalidPath { path: String, position: usize, message: String },
+    /// An error raised while decoding, with the position where it was detected. `path` names the
+    /// field that was being decoded, e.g. `Level.Sections[3].BlockStates`, and `tag` its type.
+    At {
+        offset: u64,
+        path: String,
+        tag: u8,
+        source: Box<NbtError>,
+    },
+}
+
+impl NbtError {
+    /// The underlying error, without position context.
+    pub fn kind(&self) -> &NbtError {
+        match self {
+            NbtError::At { source, .. } => source.kind(),
+            e => e,
+        }
+    }
+
+    /// Adds position context, unless the error already has it.
+    pub(crate) fn at(self, offset: u64, tag: u8) -> NbtError {
+        match self {
+            NbtError::At { .. } => self,
+            e => NbtError::At {
+                offset,
+                path: String::new(),
+                tag,
+                source: Box::new(e),
+            },
+        }
+    }
+
+    /// Prefixes the path of a positioned error with the name of the enclosing field.
+    pub(crate) fn in_field(mut self, name: &str) -> NbtError {
+        if let NbtError::At { path, .. } = &mut self {
+            let name = quote_name(name);
+            *path = match path.chars().next() {
+                None | Some('[') => format!("{}{}", name, path),
+                Some(_) => format!("{}.{}", name, path),
+            };
+        }
+        self
+    }
+
+    /// Prefixes the path of a positioned error with a list or array index.
+    pub(crate) fn in_element(mut self, index: usize) -> NbtError {
+        if let NbtError::At { path, .. } = &mut self {
+            *path = match path.chars().next() {
+                None | Some('[') => format!("[{}]{}", index, path),
+                Some(_) => format!("[{}].{}", index, path),
+            };
+        }
+        self
+    }
+}
+
+/// Appends a field name to a path, e.g. `Level` to `Level.Sections[3]`.
+pub(crate) fn push_field(path: &mut String, name: &str) {
+    if !path.is_empty() {
+        path.push('.');
+    }
+    path.push_str(&quote_name(name));
+}
+
+/// Appends a list or array index to a path.
+pub(crate) fn push_index(path: &mut String, index: usize) {
+    path.push_str(&format!("[{}]", index));
+}
+
+fn quote_name(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(is_plain_char) {
+        name.to_string()
+    } else {
+        format!("{:?}", name)
+    }
+}
+
+fn is_plain_char(c: char) -> bool {
+    !matches!(c, '.' | '[' | ']' | '"' | ' ')
+}
+
+impl std::fmt::Display for NbtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NbtError::RootNotCompoundError => write!(f, "root tag is not a compound"),
+            NbtError::IOError(e) => write!(f, "io error: {}", e),
+            NbtError::Utf8Error(e) => write!(f, "invalid string: {}", e),
+            NbtError::UnknownChunkCompression(id) => {
+                write!(f, "unknown chunk compression type {}", id)
+            }
+            NbtError::CorruptChunk(message) => write!(f, "corrupt chunk: {}", message),
+            NbtError::ChunkTooLarge(len) => {
+                write!(f, "chunk of {} bytes does not fit into the region file", len)
+            }
+            NbtError::ExternalChunkUnavailable(x, z) => {
+                write!(
+                    f,
+                    "chunk ({}, {}) is too large but the region has no directory for .mcc files",
+                    x, z
+                )
+            }
+            NbtError::SnbtParseError { position, message } => {
+                write!(f, "invalid SNBT at position {}: {}", position, message)
+            }
+            NbtError::SerdeError(message) => write!(f, "{}", message),
+            NbtError::UnknownTag(tag) => write!(f, "unknown tag id {}", tag),
+            NbtError::NegativeLength(len) => write!(f, "negative length {}", len),
+            NbtError::LimitExceeded(ReadLimit::Depth(max)) => {
+                write!(f, "nesting exceeds the maximum depth of {}", max)
+            }
+            NbtError::LimitExceeded(ReadLimit::Alloc(max)) => {
+                write!(f, "allocation exceeds the limit of {} bytes", max)
+            }
+            NbtError::LimitExceeded(ReadLimit::Size(max)) => {
+                write!(f, "input exceeds the limit of {} bytes", max)
+            }
+            NbtError::InvalidListType(tag) => write!(f, "invalid list element type {}", tag),
+            NbtError::UnexpectedEof => write!(f, "unexpected end of data"),
+            NbtError::InvalidWrite(message) => write!(f, "invalid write: {}", message),
+            NbtError::Mutf8Error(index) => {
+                write!(f, "invalid modified UTF-8 in string at index {}", index)
+            }
+            NbtError::StringTooLong { path, len } => {
+                write!(f, "string of {} bytes is too long", len)?;
+                write_path(f, path)
+            }
+            NbtError::ArrayTooLong { path, len } => {
+                write!(f, "array or list of {} elements is too long", len)?;
+                write_path(f, path)
+            }
+            NbtError::MixedList {
+                path,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "list element has tag {} instead of the list type {}",
+                    found, expected
+                )?;
+                write_path(f, path)
+            }
+            NbtError::MisplacedEnd { path } => {
+                write!(f, "TAG_End can only be written as an empty root")?;
+                write_path(f, path)
+            }
+            NbtError::InvalidVarInt => write!(f, "VarInt is too long"),
+            NbtError::DuplicateKey(name) => write!(f, "duplicate field {:?}", name),
+            NbtError::InvalidPath {
+                path,
+                position,
+                message,
+            } => write!(
+                f,
+                "invalid NBT path {:?} at position {}: {}",
+                path, position, message
+            ),
+            NbtError::At {
+                offset,
+                path,
+                tag,
+                source,
+            } => {
+                write!(f, "{} at byte {}", source, offset)?;
+                write_path(f, path)?;
+                write!(f, " (tag {})", tag)
+            }
+        }
+    }
+}
+
+fn write_path(f: &mut std::fmt::Formatter<'_>, path: &str) -> std::fmt::Result {
+    if !path.is_empty() {
+        write!(f, " in {}", path)?;
+    }
+    Ok(())
+}
+
+impl std::error::Error for NbtError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NbtError::IOError(e) => Some(e),
+            NbtError::Utf8Error(e) => Some(e),
+            NbtError::At { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl serde::ser::Error for NbtError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        NbtError::SerdeError(msg.to_string())
+    }
+}
+
+impl serde::de::Error for NbtError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        NbtError::SerdeError(msg.to_string())
+    }
 }
 
 impl From<std::io::Error> for NbtError {
     fn from(value: std::io::Error) -> Self {
-        NbtError::IOError(value)
+        match value.kind() {
+            std::io::ErrorKind::UnexpectedEof => NbtError::UnexpectedEof,
+            _ => NbtError::IOError(value),
+        }
     }
 }
 
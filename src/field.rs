@@ -1,13 +1,17 @@
-use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::borrow::Cow;
 use std::io::{Read, Write};
 
-use crate::utils::{
-    read_compound, read_list, read_name, read_string, write_list, write_string, TagWrite,
-    TagWriteFull,
-};
+use crate::compound::NbtCompound;
+use crate::decode::{Decoder, ReadOptions};
+use crate::encode::{Encoder, Encoding, WriteOptions};
+use crate::entry::Entry;
+use crate::path::{NbtPath, NbtValueMut};
+use crate::snbt::SnbtWriter;
+use crate::utils::{write_list, TagWrite, TagWriteFull};
+use crate::validate::Validator;
 use crate::value::{
     NbtValue, TAG_BYTE, TAG_BYTE_ARRAY, TAG_COMPOUND, TAG_DOUBLE, TAG_END, TAG_FLOAT, TAG_INT,
-    TAG_INT_ARRAY, TAG_LIST, TAG_LONG, TAG_LONG_ARRAY, TAG_SHORT, TAG_STRING,
+    TAG_INT_ARRAY, TAG_LONG, TAG_LONG_ARRAY, TAG_SHORT, TAG_STRING,
 };
 
 use crate::{NbtError, NbtList};
@@ -20,7 +24,7 @@ pub struct NbtField {
 
 // ---- Constructors -------------------------------------------------------------------------------
 impl NbtField {
-    pub fn new_compound<S: Into<String>, F: Into<Vec<NbtField>>>(name: S, fields: F) -> NbtField {
+    pub fn new_compound<S: Into<String>, F: Into<NbtCompound>>(name: S, fields: F) -> NbtField {
         NbtField {
             name: name.into(),
             value: NbtValue::Compound(fields.into()),
@@ -110,18 +114,17 @@ impl NbtField {
         }
     }
 
-    pub fn as_compound(&self) -> Option<&Vec<NbtField>> {
+    pub fn as_compound(&self) -> Option<&NbtCompound> {
         match &self.value {
             NbtValue::Compound(f) => Some(f),
             _ => None,
         }
     }
 
+    /// Accepts `Boolean` values as well as `Byte` values of 0 or 1, which is how booleans are
+    /// stored in files.
     pub fn as_bool(&self) -> Option<bool> {
-        match &self.value {
-            NbtValue::Boolean(b) => Some(*b),
-            _ => None,
-        }
+        self.value.as_bool()
     }
 
     pub fn as_float(&self) -> Option<f32> {
@@ -179,11 +182,58 @@ impl NbtField {
             _ => None,
         }
     }
+
+    pub fn as_compound_mut(&mut self) -> Option<&mut NbtCompound> {
+        match &mut self.value {
+            NbtValue::Compound(f) => Some(f),
+            _ => None,
+        }
+    }
+
+    pub fn as_list_mut(&mut self) -> Option<&mut NbtList> {
+        match &mut self.value {
+            NbtValue::List(l) => Some(l),
+            _ => None,
+        }
+    }
 }
 
 // ---- Read Write impls ---------------------------------------------------------------------------
 impl NbtField {
-    pub fn write<T: TagWrite, W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+    /// Writes this field, failing before anything is written if it cannot be represented as
+    /// NBT (see [`NbtField::validate`]).
+    pub fn write<T: TagWrite, W: Write>(&self, w: &mut W) -> Result<(), NbtError> {
+        self.write_with_options::<T, W>(w, &WriteOptions::default())
+    }
+
+    /// Like [`NbtField::write`], in the encoding given by `options`.
+    pub fn write_with_options<T: TagWrite, W: Write>(
+        &self,
+        w: &mut W,
+        options: &WriteOptions,
+    ) -> Result<(), NbtError> {
+        if let Some(problem) = Validator::first_problem(self, options.encoding) {
+            return Err(problem);
+        }
+        Ok(self.write_unchecked::<T, _>(&mut Encoder::new(w, options.encoding))?)
+    }
+
+    /// Checks that this field can be written: strings and names must fit into 65535 bytes,
+    /// arrays and lists into `i32::MAX` elements, lists of lists or compounds may not contain
+    /// other values, and only an empty root may be `NbtValue::End`. Returns every problem found.
+    pub fn validate(&self) -> Result<(), Vec<NbtError>> {
+        let problems = Validator::problems(self, Encoding::BigEndian);
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    pub(crate) fn write_unchecked<T: TagWrite, W: Write>(
+        &self,
+        w: &mut Encoder<W>,
+    ) -> std::io::Result<()> {
         match &self.value {
             NbtValue::Byte(b) => {
                 T::write(w, TAG_BYTE, &self.name)?;
@@ -195,54 +245,54 @@ impl NbtField {
             }
             NbtValue::Short(s) => {
                 T::write(w, TAG_SHORT, &self.name)?;
-                w.write_i16::<BigEndian>(*s)
+                w.write_i16(*s)
             }
             NbtValue::Int(i) => {
                 T::write(w, TAG_INT, &self.name)?;
-                w.write_i32::<BigEndian>(*i)
+                w.write_i32(*i)
             }
             NbtValue::Long(l) => {
                 T::write(w, TAG_LONG, &self.name)?;
-                w.write_i64::<BigEndian>(*l)
+                w.write_i64(*l)
             }
             NbtValue::Float(f) => {
                 T::write(w, TAG_FLOAT, &self.name)?;
-                w.write_f32::<BigEndian>(*f)
+                w.write_f32(*f)
             }
             NbtValue::Double(d) => {
                 T::write(w, TAG_DOUBLE, &self.name)?;
-                w.write_f64::<BigEndian>(*d)
+                w.write_f64(*d)
             }
             NbtValue::String(s) => {
                 T::write(w, TAG_STRING, &self.name)?;
-                write_string(s, w)
+                w.write_string(s)
             }
             NbtValue::List(l) => write_list::<T, W>(w, l, &self.name),
             NbtValue::Compound(c) => {
                 T::write(w, TAG_COMPOUND, &self.name)?;
                 for value in c {
-                    value.write::<TagWriteFull, W>(w)?;
+                    value.write_unchecked::<TagWriteFull, W>(w)?;
                 }
                 w.write_u8(TAG_END)
             }
             NbtValue::ByteArray(arr) => {
                 T::write(w, TAG_BYTE_ARRAY, &self.name)?;
-                w.write_i32::<BigEndian>(arr.len() as i32)?;
-                w.write_all(arr)
+                w.write_len(arr.len())?;
+                w.write_bytes(arr)
             }
             NbtValue::IntArray(arr) => {
                 T::write(w, TAG_INT_ARRAY, &self.name)?;
-                w.write_i32::<BigEndian>(arr.len() as i32)?;
+                w.write_len(arr.len())?;
                 for i in arr {
-                    w.write_i32::<BigEndian>(*i)?;
+                    w.write_i32(*i)?;
                 }
                 Ok(())
             }
             NbtValue::LongArray(arr) => {
                 T::write(w, TAG_LONG_ARRAY, &self.name)?;
-                w.write_i32::<BigEndian>(arr.len() as i32)?;
+                w.write_len(arr.len())?;
                 for i in arr {
-                    w.write_i64::<BigEndian>(*i)?;
+                    w.write_i64(*i)?;
                 }
                 Ok(())
             }
@@ -251,115 +301,115 @@ impl NbtField {
     }
 
     pub fn read<R: Read>(r: &mut R) -> Result<NbtField, NbtError> {
-        let tag = r.read_u8()?;
-
-        Ok(match tag {
-            TAG_END => NbtField {
-                name: "".to_string(),
-                value: NbtValue::End,
-            },
-            TAG_BYTE => NbtField {
-                name: read_name(r)?,
-                value: NbtValue::Byte(r.read_u8()?),
-            },
-            TAG_SHORT => NbtField {
-                name: read_name(r)?,
-                value: NbtValue::Short(r.read_i16::<BigEndian>()?),
-            },
-            TAG_INT => NbtField {
-                name: read_name(r)?,
-                value: NbtValue::Int(r.read_i32::<BigEndian>()?),
-            },
-            TAG_LONG => NbtField {
-                name: read_name(r)?,
-                value: NbtValue::Long(r.read_i64::<BigEndian>()?),
-            },
-            TAG_FLOAT => NbtField {
-                name: read_name(r)?,
-                value: NbtValue::Float(r.read_f32::<BigEndian>()?),
-            },
-            TAG_DOUBLE => NbtField {
-                name: read_name(r)?,
-                value: NbtValue::Double(r.read_f64::<BigEndian>()?),
-            },
-            TAG_BYTE_ARRAY => {
-                let name = read_name(r)?;
-                let len = r.read_i32::<BigEndian>()?;
-                let mut buf = vec![0; len as usize];
-                _ = r.read(&mut buf)?;
-                NbtField {
-                    name,
-                    value: NbtValue::ByteArray(buf),
-                }
-            }
-            TAG_STRING => {
-                let name = read_name(r)?;
-                NbtField {
-                    name,
-                    value: NbtValue::String(read_string(r)?),
-                }
-            }
-            TAG_LIST => {
-                let name = read_name(r)?;
-                NbtField {
-                    name,
-                    value: read_list(r)?,
-                }
-            }
-            TAG_INT_ARRAY => {
-                let name = read_name(r)?;
-                let len = r.read_i32::<BigEndian>()?;
-                let mut buf = Vec::with_capacity(len as usize);
-                for _ in 0..len {
-                    buf.push(r.read_i32::<BigEndian>()?);
-                }
-                NbtField {
-                    name,
-                    value: NbtValue::IntArray(buf),
-                }
-            }
-            TAG_COMPOUND => {
-                let name = read_name(r)?;
-                NbtField {
-                    name,
-                    value: read_compound(r)?,
-                }
-            }
-            TAG_LONG_ARRAY => {
-                let name = read_name(r)?;
-                let len = r.read_i32::<BigEndian>()?;
-                let mut buf = Vec::with_capacity(len as usize);
-                for _ in 0..len {
-                    buf.push(r.read_i64::<BigEndian>()?);
-                }
-                NbtField {
-                    name,
-                    value: NbtValue::LongArray(buf),
-                }
-            }
-            _ => panic!("Unknown tag: {}", tag),
-        })
+        Self::read_with_options(r, &ReadOptions::default())
+    }
+
+    /// Reads an uncompressed field, failing with `NbtError::LimitExceeded` if it exceeds one
+    /// of the `options` limits.
+    pub fn read_with_options<R: Read>(
+        r: &mut R,
+        options: &ReadOptions,
+    ) -> Result<NbtField, NbtError> {
+        Decoder::new(r, options).read_root()
+    }
+
+    // ---- SNBT -----------------------------------------------------------------------------------
+    /// The value of this field as SNBT. The name is dropped, as SNBT has no notion of a named root.
+    pub fn to_snbt(&self) -> String {
+        self.value.to_snbt()
+    }
+
+    pub fn to_snbt_pretty(&self) -> String {
+        self.value.to_snbt_pretty()
     }
 
     // ---- Element Access -------------------------------------------------------------------------
+    /// Follows `path` through nested compounds.
     pub fn get_path(&self, path: &[&str]) -> Option<&NbtField> {
-        let path = path.iter();
-        let mut child = Some(self);
+        let mut child = self;
         for name in path {
-            if let Some(c) = child {
-                child = c.get(name);
-            } else {
-                return None;
-            }
+            child = child.get(name)?;
+        }
+        Some(child)
+    }
+
+    /// Like [`NbtField::get_path`], but in a list of compounds a segment selects the element at
+    /// that index, e.g. `["Inventory", "0", "id"]`. See [`NbtField::query`] for full NBT paths.
+    pub fn get_indexed_path(&self, path: &[&str]) -> Option<&NbtField> {
+        let mut child = self;
+        for key in path {
+            child = child.child(key)?;
         }
-        child
+        Some(child)
     }
 
     pub fn get(&self, name: &str) -> Option<&NbtField> {
+        self.value.get(name)
+    }
+
+    /// Every value that `path` selects, in document order. Values inside compounds and lists of
+    /// lists or compounds are borrowed, elements of other lists and of arrays are copied.
+    pub fn query(&self, path: &NbtPath) -> Vec<Cow<'_, NbtValue>> {
+        path.select(self)
+    }
+
+    /// Like [`NbtField::query`], returning mutable references.
+    pub fn query_mut(&mut self, path: &NbtPath) -> Vec<NbtValueMut<'_>> {
+        path.select_mut(self)
+    }
+
+    fn child(&self, key: &str) -> Option<&NbtField> {
         match &self.value {
-            NbtValue::Compound(fields) => fields.iter().find(|f| f.name == name),
-            _ => None,
+            NbtValue::List(NbtList::Compound(items)) => items.get(key.parse::<usize>().ok()?),
+            value => value.get(key),
+        }
+    }
+
+    // ---- Mutation -------------------------------------------------------------------------------
+    /// The value of a field of this compound. Names cannot be changed in place, see
+    /// [`NbtCompound`].
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut NbtValue> {
+        self.value.get_mut(name)
+    }
+
+    /// Like [`NbtField::get_path`], returning the value the path leads to.
+    pub fn get_path_mut(&mut self, path: &[&str]) -> Option<&mut NbtValue> {
+        let mut value = &mut self.value;
+        for name in path {
+            value = value.get_mut(name)?;
+        }
+        Some(value)
+    }
+
+    /// Like [`NbtField::get_indexed_path`], returning the value the path leads to.
+    pub fn get_indexed_path_mut(&mut self, path: &[&str]) -> Option<&mut NbtValue> {
+        let mut value = &mut self.value;
+        for key in path {
+            value = match value {
+                NbtValue::List(NbtList::Compound(items)) => {
+                    &mut items.get_mut(key.parse::<usize>().ok()?)?.value
+                }
+                value => value.get_mut(key)?,
+            };
         }
+        Some(value)
+    }
+
+    /// Sets a field of this compound, replacing the field of the same name in place or appending
+    /// it. Returns the replaced field, or `NbtError::InvalidWrite` if this is not a compound.
+    pub fn insert(&mut self, field: NbtField) -> Result<Option<NbtField>, NbtError> {
+        self.value.insert(field)
+    }
+
+    /// Removes a field of this compound, keeping the order of the remaining fields.
+    pub fn remove(&mut self, name: &str) -> Option<NbtField> {
+        self.value.remove(name)
+    }
+
+    /// The field `name` of this compound, for in-place updates and inserts. `None` if this is not
+    /// a compound.
+    pub fn entry(&mut self, name: &str) -> Option<Entry<'_>> {
+        self.value.entry(name)
     }
 
     // ---- Convenience Access ---------------------------------------------------------------------
@@ -376,11 +426,9 @@ impl NbtField {
         })
     }
 
+    /// See [`NbtField::as_bool`].
     pub fn get_bool(&self, name: &str) -> Option<bool> {
-        self.get(name).and_then(|f| match &f.value {
-            NbtValue::Boolean(b) => Some(*b),
-            _ => None,
-        })
+        self.get(name).and_then(|f| f.value.as_bool())
     }
     pub fn get_float(&self, name: &str) -> Option<f32> {
         self.get(name).and_then(|f| match &f.value {
@@ -409,6 +457,7 @@ impl NbtField {
     pub fn get_byte(&self, name: &str) -> Option<u8> {
         self.get(name).and_then(|f| match &f.value {
             NbtValue::Byte(b) => Some(*b),
+            NbtValue::Boolean(b) => Some(*b as u8),
             _ => None,
         })
     }
@@ -436,7 +485,7 @@ impl NbtField {
             _ => None,
         })
     }
-    pub fn get_compound(&self, name: &str) -> Option<&Vec<NbtField>> {
+    pub fn get_compound(&self, name: &str) -> Option<&NbtCompound> {
         self.get(name).and_then(|f| match &f.value {
             NbtValue::Compound(c) => Some(c),
             _ => None,
@@ -445,7 +494,8 @@ impl NbtField {
 }
 
 impl std::fmt::Display for NbtField {
+    /// Formats the field as a compound entry `name:value` in SNBT.
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}: {}", self.name, self.value)
+        SnbtWriter::new(f, f.alternate()).write_field(self)
     }
 }
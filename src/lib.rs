@@ -1,28 +1,173 @@
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::io::{Read, Write};
-use utils::TagWriteFull;
+use utils::{TagWriteFull, TagWriteUnnamed};
 
+mod arrays;
+mod borrowed;
+mod compound;
+mod compression;
+mod de;
+mod decode;
+mod diff;
+mod encode;
+mod entry;
 mod error;
 mod field;
 mod list;
+mod mutf8;
+mod path;
+mod reader;
+mod region;
+mod ser;
+mod snbt;
 mod utils;
+mod validate;
 mod value;
+mod writer;
 
+pub use arrays::{byte_array, int_array, long_array, ByteArray, IntArray, LongArray};
+pub use borrowed::{BeElement, BeSlice, CompoundIter, ListIter, ListRef, NbtRef};
+pub use compound::NbtCompound;
+pub use compression::{Compression, DEFAULT_COMPRESSION_LEVEL};
+pub use de::from_value;
+pub use decode::{DuplicateKeys, ReadLimit, ReadOptions};
+pub use diff::{diff, render_diff, NbtChange};
+pub use encode::{Encoding, WriteOptions};
+pub use entry::Entry;
 pub use error::NbtError;
 pub use field::NbtField;
 pub use list::NbtList;
-pub use value::NbtValue;
+pub use path::{NbtPath, NbtValueMut};
+pub use reader::{NbtEvent, NbtReader};
+pub use region::{Chunks, Region};
+pub use ser::to_value;
+pub use value::{
+    NbtValue, TAG_BYTE, TAG_BYTE_ARRAY, TAG_COMPOUND, TAG_DOUBLE, TAG_END, TAG_FLOAT, TAG_INT,
+    TAG_INT_ARRAY, TAG_LIST, TAG_LONG, TAG_LONG_ARRAY, TAG_SHORT, TAG_STRING,
+};
+pub use writer::NbtWriter;
 
 // ---- public functions ---------------------------------------------------------------------------
 
 pub fn write_nbt<W: Write>(w: &mut W, root: &NbtField) -> Result<(), NbtError> {
-    Ok(root.write::<TagWriteFull, W>(w)?)
+    root.write::<TagWriteFull, W>(w)
 }
 
+/// Writes `root` uncompressed in the encoding given by `options`.
+pub fn write_nbt_with_options<W: Write>(
+    w: &mut W,
+    root: &NbtField,
+    options: &WriteOptions,
+) -> Result<(), NbtError> {
+    root.write_with_options::<TagWriteFull, W>(w, options)
+}
+
+/// Writes `root` wrapped in the given compression, e.g. `Compression::gzip()` for level.dat.
+pub fn write_nbt_compressed<W: Write>(
+    w: &mut W,
+    root: &NbtField,
+    compression: Compression,
+) -> Result<(), NbtError> {
+    compression::encode(w, compression, |mut w| write_nbt(&mut w, root))
+}
+
+/// Reads an NBT tree, transparently decompressing gzip and zlib streams.
 pub fn read_nbt<R: Read>(r: &mut R) -> Result<NbtField, NbtError> {
-    NbtField::read(r)
+    let mut r = compression::auto_decoder(r)?;
+    NbtField::read(&mut r)
+}
+
+/// Reads an NBT tree like [`read_nbt`], enforcing the limits in `options` on the decompressed data.
+pub fn read_nbt_with_options<R: Read>(
+    r: &mut R,
+    options: &ReadOptions,
+) -> Result<NbtField, NbtError> {
+    let mut r = compression::auto_decoder(r)?;
+    NbtField::read_with_options(&mut r, options)
 }
 
 pub fn from_bytes(bytes: &[u8]) -> Result<NbtField, NbtError> {
     let mut r = std::io::Cursor::new(bytes);
     read_nbt(&mut r)
-}
\ No newline at end of file
+}
+
+pub fn from_bytes_with_options(bytes: &[u8], options: &ReadOptions) -> Result<NbtField, NbtError> {
+    let mut r = std::io::Cursor::new(bytes);
+    read_nbt_with_options(&mut r, options)
+}
+
+// ---- Java protocol ------------------------------------------------------------------------------
+// Since 1.20.2 the Java protocol sends the root without a name, and some packets allow roots of
+// any type, e.g. a plain string for chat components.
+
+/// Reads an uncompressed root that has a tag but no name. The returned field is unnamed.
+pub fn read_unnamed_nbt<R: Read>(r: &mut R) -> Result<NbtField, NbtError> {
+    read_unnamed_nbt_with_options(r, &ReadOptions::default())
+}
+
+/// Like [`read_unnamed_nbt`], enforcing the limits in `options`.
+pub fn read_unnamed_nbt_with_options<R: Read>(
+    r: &mut R,
+    options: &ReadOptions,
+) -> Result<NbtField, NbtError> {
+    decode::Decoder::new(r, options).read_unnamed_root()
+}
+
+/// Writes `root` uncompressed without its name, see [`read_unnamed_nbt`].
+pub fn write_unnamed_nbt<W: Write>(w: &mut W, root: &NbtField) -> Result<(), NbtError> {
+    root.write::<TagWriteUnnamed, W>(w)
+}
+
+// ---- Bedrock ------------------------------------------------------------------------------------
+
+/// Reads a Bedrock level.dat: a little-endian storage version and payload length, followed by
+/// little-endian NBT. Returns the storage version and the root.
+pub fn read_bedrock_level_dat<R: Read>(r: &mut R) -> Result<(i32, NbtField), NbtError> {
+    let version = r.read_i32::<LittleEndian>()?;
+    let len = r.read_u32::<LittleEndian>()? as u64;
+    let options = ReadOptions::default()
+        .encoding(Encoding::LittleEndian)
+        .max_size(len);
+    let root = NbtField::read_with_options(&mut r.take(len), &options)?;
+    Ok((version, root))
+}
+
+/// Writes a Bedrock level.dat with the given storage version, see [`read_bedrock_level_dat`].
+pub fn write_bedrock_level_dat<W: Write>(
+    w: &mut W,
+    version: i32,
+    root: &NbtField,
+) -> Result<(), NbtError> {
+    let mut data = Vec::new();
+    let options = WriteOptions::default().encoding(Encoding::LittleEndian);
+    write_nbt_with_options(&mut data, root, &options)?;
+    let len = u32::try_from(data.len()).map_err(|_| NbtError::ArrayTooLong {
+        path: String::new(),
+        len: data.len(),
+    })?;
+    w.write_i32::<LittleEndian>(version)?;
+    w.write_u32::<LittleEndian>(len)?;
+    Ok(w.write_all(&data)?)
+}
+
+/// Parses an SNBT string such as `{Count:1b,id:"minecraft:stone"}` into an unnamed field.
+pub fn from_snbt(snbt: &str) -> Result<NbtField, NbtError> {
+    Ok(NbtField {
+        name: String::new(),
+        value: snbt::parse_snbt(snbt)?,
+    })
+}
+
+/// Serializes `value` into an unnamed root and writes it uncompressed.
+pub fn to_writer<W: Write, T: serde::Serialize + ?Sized>(
+    w: &mut W,
+    value: &T,
+) -> Result<(), NbtError> {
+    write_nbt(w, &to_value(value)?)
+}
+
+/// Reads an NBT tree like [`read_nbt`] and deserializes it into `T`.
+pub fn from_reader<R: Read, T: serde::de::DeserializeOwned>(r: &mut R) -> Result<T, NbtError> {
+    let root = read_nbt(r)?;
+    from_value(&root)
+}
@@ -1,6 +1,8 @@
+use crate::snbt::SnbtWriter;
 use crate::{field::NbtField, value::NbtValue};
 
-#[derive(Debug, PartialEq, Clone)]
+/// A NBT list. A `Boolean` list is written as a `Byte` list and compares equal to it.
+#[derive(Debug, Clone)]
 pub enum NbtList {
     Byte(Vec<u8>),
     Boolean(Vec<bool>),
@@ -12,12 +14,71 @@ pub enum NbtList {
     String(Vec<String>),
     List(Vec<NbtValue>),
     Compound(Vec<NbtField>),
+    ByteArray(Vec<Vec<u8>>),
     LongArray(Vec<Vec<i64>>),
     IntArray(Vec<Vec<i32>>),
     End,
 }
 
 impl NbtList {
+    /// Builds a list from loose values, returning `None` if they do not share a single type.
+    /// Booleans mixed with bytes are stored as bytes.
+    pub fn from_values(values: Vec<NbtValue>) -> Option<NbtList> {
+        macro_rules! collect {
+            ($variant:ident) => {
+                values
+                    .into_iter()
+                    .map(|v| match v {
+                        NbtValue::$variant(v) => Some(v),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .map(NbtList::$variant)
+            };
+        }
+
+        let first = match values.first() {
+            Some(first) => std::mem::discriminant(first),
+            None => return Some(NbtList::End),
+        };
+        if !values.iter().all(|v| std::mem::discriminant(v) == first) {
+            let bytes = values
+                .iter()
+                .map(|v| match v {
+                    NbtValue::Byte(b) => Some(*b),
+                    NbtValue::Boolean(b) => Some(*b as u8),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            return bytes.map(NbtList::Byte);
+        }
+
+        match values[0] {
+            NbtValue::Byte(_) => collect!(Byte),
+            NbtValue::Boolean(_) => collect!(Boolean),
+            NbtValue::Short(_) => collect!(Short),
+            NbtValue::Int(_) => collect!(Int),
+            NbtValue::Long(_) => collect!(Long),
+            NbtValue::Float(_) => collect!(Float),
+            NbtValue::Double(_) => collect!(Double),
+            NbtValue::String(_) => collect!(String),
+            NbtValue::ByteArray(_) => collect!(ByteArray),
+            NbtValue::IntArray(_) => collect!(IntArray),
+            NbtValue::LongArray(_) => collect!(LongArray),
+            NbtValue::List(_) => Some(NbtList::List(values)),
+            NbtValue::Compound(_) => Some(NbtList::Compound(
+                values
+                    .into_iter()
+                    .map(|value| NbtField {
+                        name: String::new(),
+                        value,
+                    })
+                    .collect(),
+            )),
+            NbtValue::End => None,
+        }
+    }
+
     pub fn as_byte_list(&self) -> Option<&Vec<u8>> {
         match self {
             NbtList::Byte(b) => Some(b),
@@ -78,6 +139,18 @@ impl NbtList {
             _ => None,
         }
     }
+    pub fn as_compound_list_mut(&mut self) -> Option<&mut Vec<NbtField>> {
+        match self {
+            NbtList::Compound(c) => Some(c),
+            _ => None,
+        }
+    }
+    pub fn as_byte_array_list(&self) -> Option<&Vec<Vec<u8>>> {
+        match self {
+            NbtList::ByteArray(b) => Some(b),
+            _ => None,
+        }
+    }
     pub fn as_long_array_list(&self) -> Option<&Vec<Vec<i64>>> {
         match self {
             NbtList::LongArray(l) => Some(l),
@@ -92,79 +165,34 @@ impl NbtList {
     }
 }
 
-impl std::fmt::Display for NbtList {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "[")?;
-        match self {
-            NbtList::Byte(b) => {
-                for b in b {
-                    write!(f, "{}, ", b)?;
-                }
-            }
-            NbtList::Boolean(b) => {
-                for b in b {
-                    write!(f, "{}, ", b)?;
-                }
-            }
-            NbtList::Short(s) => {
-                for s in s {
-                    write!(f, "{}, ", s)?;
-                }
-            }
-            NbtList::Int(i) => {
-                for i in i {
-                    write!(f, "{}, ", i)?;
-                }
-            }
-            NbtList::Long(l) => {
-                for l in l {
-                    write!(f, "{}, ", l)?;
-                }
-            }
-            NbtList::Float(float) => {
-                for float in float {
-                    write!(f, "{}, ", float)?;
-                }
-            }
-            NbtList::Double(d) => {
-                for d in d {
-                    write!(f, "{}, ", d)?;
-                }
-            }
-            NbtList::String(s) => {
-                for s in s {
-                    write!(f, "{}, ", s)?;
-                }
-            }
-            NbtList::List(l) => {
-                for l in l {
-                    write!(f, "{}, ", l)?;
-                }
-            }
-            NbtList::Compound(c) => {
-                for c in c {
-                    write!(f, "{}, ", c)?;
-                }
-            }
-            NbtList::LongArray(list) => {
-                for l in list {
-                    for l in l {
-                        write!(f, "{}, ", l)?;
-                    }
-                }
-            }
-            NbtList::IntArray(list) => {
-                for l in list {
-                    for l in l {
-                        write!(f, "{}, ", l)?;
-                    }
-                }
-            }
-            NbtList::End => {
-                write!(f, "Empty")?;
+impl PartialEq for NbtList {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (NbtList::Byte(a), NbtList::Byte(b)) => a == b,
+            (NbtList::Boolean(a), NbtList::Boolean(b)) => a == b,
+            (NbtList::Byte(a), NbtList::Boolean(b)) | (NbtList::Boolean(b), NbtList::Byte(a)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| *a == *b as u8)
             }
+            (NbtList::Short(a), NbtList::Short(b)) => a == b,
+            (NbtList::Int(a), NbtList::Int(b)) => a == b,
+            (NbtList::Long(a), NbtList::Long(b)) => a == b,
+            (NbtList::Float(a), NbtList::Float(b)) => a == b,
+            (NbtList::Double(a), NbtList::Double(b)) => a == b,
+            (NbtList::String(a), NbtList::String(b)) => a == b,
+            (NbtList::List(a), NbtList::List(b)) => a == b,
+            (NbtList::Compound(a), NbtList::Compound(b)) => a == b,
+            (NbtList::ByteArray(a), NbtList::ByteArray(b)) => a == b,
+            (NbtList::IntArray(a), NbtList::IntArray(b)) => a == b,
+            (NbtList::LongArray(a), NbtList::LongArray(b)) => a == b,
+            (NbtList::End, NbtList::End) => true,
+            _ => false,
         }
+    }
+}
 
-        write!(f, "]")
+impl std::fmt::Display for NbtList {
+    /// Formats the list as SNBT, `{:#}` pretty-prints it over multiple lines.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        SnbtWriter::new(f, f.alternate()).write_list(self)
     }
 }
@@ -0,0 +1,146 @@
+use std::borrow::Cow;
+
+use crate::error::NbtError;
+
+// ---- Modified UTF-8 -----------------------------------------------------------------------------
+// Java encodes NUL as `C0 80` and characters outside the BMP as two 3-byte surrogates. Every
+// other string is encoded exactly like UTF-8, which is what the fast paths below rely on.
+
+/// Decodes a Java modified UTF-8 string.
+///
+/// A `lenient` decoder also accepts the 4-byte sequences of standard UTF-8 and replaces invalid
+/// sequences and unpaired surrogates with U+FFFD instead of failing.
+pub(crate) fn decode(bytes: &[u8], lenient: bool) -> Result<Cow<'_, str>, NbtError> {
+    match std::str::from_utf8(bytes) {
+        Ok(s) if lenient || !has_four_byte_sequence(bytes) => Ok(Cow::Borrowed(s)),
+        _ => decode_slow(bytes, lenient).map(Cow::Owned),
+    }
+}
+
+/// Like [`decode`], reusing the buffer when it needs no conversion.
+pub(crate) fn into_string(bytes: Vec<u8>, lenient: bool) -> Result<String, NbtError> {
+    if !lenient && has_four_byte_sequence(&bytes) {
+        return decode_slow(&bytes, lenient);
+    }
+    String::from_utf8(bytes).or_else(|e| decode_slow(e.as_bytes(), lenient))
+}
+
+/// Encodes a string as Java modified UTF-8.
+pub(crate) fn encode(s: &str) -> Cow<'_, [u8]> {
+    if !needs_encoding(s) {
+        return Cow::Borrowed(s.as_bytes());
+    }
+    let mut bytes = Vec::with_capacity(encoded_len(s));
+    for c in s.chars() {
+        match c {
+            '\0' => bytes.extend_from_slice(&[0xc0, 0x80]),
+            c if c.len_utf8() == 4 => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    push_three_bytes(&mut bytes, *unit);
+                }
+            }
+            c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    Cow::Owned(bytes)
+}
+
+/// The number of bytes [`encode`] produces for `s`.
+pub(crate) fn encoded_len(s: &str) -> usize {
+    if !needs_encoding(s) {
+        return s.len();
+    }
+    s.chars()
+        .map(|c| match c {
+            '\0' => 2,
+            c if c.len_utf8() == 4 => 6,
+            c => c.len_utf8(),
+        })
+        .sum()
+}
+
+// ---- Helper functions ---------------------------------------------------------------------------
+fn needs_encoding(s: &str) -> bool {
+    s.bytes().any(|b| b == 0 || b >= 0xf0)
+}
+
+fn has_four_byte_sequence(bytes: &[u8]) -> bool {
+    bytes.iter().any(|b| *b >= 0xf0)
+}
+
+fn push_three_bytes(bytes: &mut Vec<u8>, unit: u16) {
+    bytes.extend_from_slice(&[
+        0xe0 | (unit >> 12) as u8,
+        0x80 | ((unit >> 6) & 0x3f) as u8,
+        0x80 | (unit & 0x3f) as u8,
+    ]);
+}
+
+fn decode_slow(bytes: &[u8], lenient: bool) -> Result<String, NbtError> {
+    let mut s = String::with_capacity(bytes.len());
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let (c, len) = match next_unit(bytes, pos) {
+            Some((Unit::Char(c), len)) => (Some(c), len),
+            Some((Unit::High(high), len)) => match next_unit(bytes, pos + len) {
+                Some((Unit::Low(low), low_len)) => {
+                    let c = 0x10000 + (((high as u32) - 0xd800) << 10) + ((low as u32) - 0xdc00);
+                    (char::from_u32(c), len + low_len)
+                }
+                _ => (None, len),
+            },
+            Some((Unit::Low(_), len)) => (None, len),
+            Some((Unit::FourByte(c), len)) if lenient => (Some(c), len),
+            Some((Unit::FourByte(_), _)) | None => (None, 1),
+        };
+        match c {
+            Some(c) => s.push(c),
+            None if lenient => s.push(char::REPLACEMENT_CHARACTER),
+            None => return Err(NbtError::Mutf8Error(pos)),
+        }
+        pos += len;
+    }
+    Ok(s)
+}
+
+enum Unit {
+    Char(char),
+    High(u16),
+    Low(u16),
+    FourByte(char),
+}
+
+/// Decodes the sequence at `pos` and returns it with its length, or `None` if it is invalid.
+fn next_unit(bytes: &[u8], pos: usize) -> Option<(Unit, usize)> {
+    let first = *bytes.get(pos)?;
+    let continuation = |i: usize| {
+        bytes
+            .get(pos + i)
+            .filter(|b| *b & 0xc0 == 0x80)
+            .map(|b| (b & 0x3f) as u32)
+    };
+    let (unit, len) = match first {
+        0x00..=0x7f => (first as u32, 1),
+        0xc0..=0xdf => (((first & 0x1f) as u32) << 6 | continuation(1)?, 2),
+        0xe0..=0xef => (
+            ((first & 0x0f) as u32) << 12 | continuation(1)? << 6 | continuation(2)?,
+            3,
+        ),
+        0xf0..=0xf7 => {
+            let c = ((first & 0x07) as u32) << 18
+                | continuation(1)? << 12
+                | continuation(2)? << 6
+                | continuation(3)?;
+            return Some((
+                Unit::FourByte(char::from_u32(c).filter(|c| c.len_utf8() == 4)?),
+                4,
+            ));
+        }
+        _ => return None,
+    };
+    Some(match unit {
+        0xd800..=0xdbff => (Unit::High(unit as u16), len),
+        0xdc00..=0xdfff => (Unit::Low(unit as u16), len),
+        _ => (Unit::Char(char::from_u32(unit)?), len),
+    })
+}
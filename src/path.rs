@@ -0,0 +1,503 @@
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use crate::compound::NbtCompound;
+use crate::error::NbtError;
+use crate::field::NbtField;
+use crate::list::NbtList;
+use crate::snbt::{parse_compound_at, parse_quoted_at, write_quoted, SnbtWriter};
+use crate::value::*;
+
+/// A path into an NBT tree in the syntax of the `/data` command, e.g.
+/// `Inventory[{Slot:0b}].tag.display.Name`:
+///
+/// - `name` or `"quoted name"` selects a field of a compound, `name{...}` only if the field is a
+///   compound that matches the SNBT pattern.
+/// - `[i]` selects an element of a list or array, counting from the end for negative `i`.
+/// - `[]` selects every element, `[{...}]` every compound element that matches the pattern.
+/// - `{...}` at the start selects the root if it matches the pattern.
+///
+/// A compound matches a pattern if it contains every field of the pattern with a matching value.
+/// A list matches if each element of the pattern matches one of its elements, and an empty
+/// pattern only matches an empty list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbtPath {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Root(NbtCompound),
+    Child(String, Option<NbtCompound>),
+    Index(i32),
+    All,
+    Matching(NbtCompound),
+}
+
+/// A mutable reference to a value selected by an [`NbtPath`]. Elements of typed lists and arrays
+/// are not stored as [`NbtValue`]s and are referenced directly.
+#[derive(Debug)]
+pub enum NbtValueMut<'a> {
+    Value(&'a mut NbtValue),
+    /// An element of a list of lists or compounds, with the tag all elements of that list share.
+    Element(u8, &'a mut NbtValue),
+    Byte(&'a mut u8),
+    Boolean(&'a mut bool),
+    Short(&'a mut i16),
+    Int(&'a mut i32),
+    Long(&'a mut i64),
+    Float(&'a mut f32),
+    Double(&'a mut f64),
+    String(&'a mut String),
+    ByteArray(&'a mut Vec<u8>),
+    IntArray(&'a mut Vec<i32>),
+    LongArray(&'a mut Vec<i64>),
+}
+
+// ---- Parser -------------------------------------------------------------------------------------
+impl NbtPath {
+    pub fn parse(path: &str) -> Result<NbtPath, NbtError> {
+        let mut parser = PathParser {
+            input: path,
+            pos: 0,
+        };
+        let mut nodes = vec![parser.node(true)?];
+        loop {
+            match parser.peek() {
+                None => break,
+                Some('.') => parser.pos += 1,
+                Some('[') => {}
+                Some(_) => return Err(parser.error("expected '.' or '['")),
+            }
+            nodes.push(parser.node(false)?);
+        }
+        Ok(NbtPath { nodes })
+    }
+}
+
+impl FromStr for NbtPath {
+    type Err = NbtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NbtPath::parse(s)
+    }
+}
+
+struct PathParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl PathParser<'_> {
+    fn error(&self, message: &str) -> NbtError {
+        self.error_at(self.pos, message.to_string())
+    }
+
+    fn error_at(&self, position: usize, message: String) -> NbtError {
+        NbtError::InvalidPath {
+            path: self.input.to_string(),
+            position,
+            message,
+        }
+    }
+
+    /// Reports an error of the SNBT parser, used for quoted names and patterns, as a path error.
+    fn snbt_error(&self, e: NbtError) -> NbtError {
+        match e {
+            NbtError::SnbtParseError { position, message } => self.error_at(position, message),
+            e => e,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn node(&mut self, first: bool) -> Result<Node, NbtError> {
+        match self.peek() {
+            Some('{') if first => Ok(Node::Root(self.pattern()?)),
+            Some('[') => self.element(),
+            Some('"') | Some('\'') => {
+                let (name, end) =
+                    parse_quoted_at(self.input, self.pos).map_err(|e| self.snbt_error(e))?;
+                self.pos = end;
+                self.child(name)
+            }
+            _ => {
+                let start = self.pos;
+                while let Some(c) = self.peek().filter(|c| is_name_char(*c)) {
+                    self.pos += c.len_utf8();
+                }
+                if self.pos == start {
+                    return Err(self.error("expected name"));
+                }
+                self.child(self.input[start..self.pos].to_string())
+            }
+        }
+    }
+
+    fn child(&mut self, name: String) -> Result<Node, NbtError> {
+        let pattern = match self.peek() {
+            Some('{') => Some(self.pattern()?),
+            _ => None,
+        };
+        Ok(Node::Child(name, pattern))
+    }
+
+    fn element(&mut self) -> Result<Node, NbtError> {
+        self.pos += 1;
+        let node = match self.peek() {
+            Some(']') => Node::All,
+            Some('{') => Node::Matching(self.pattern()?),
+            _ => {
+                let start = self.pos;
+                while let Some(c) = self.peek().filter(|c| *c == '-' || c.is_ascii_digit()) {
+                    self.pos += c.len_utf8();
+                }
+                let index = self.input[start..self.pos]
+                    .parse()
+                    .map_err(|_| self.error_at(start, "expected index".to_string()))?;
+                Node::Index(index)
+            }
+        };
+        if self.peek() != Some(']') {
+            return Err(self.error("expected ']'"));
+        }
+        self.pos += 1;
+        Ok(node)
+    }
+
+    fn pattern(&mut self) -> Result<NbtCompound, NbtError> {
+        let (pattern, end) =
+            parse_compound_at(self.input, self.pos).map_err(|e| self.snbt_error(e))?;
+        self.pos = end;
+        Ok(pattern)
+    }
+}
+
+// ---- Evaluation ---------------------------------------------------------------------------------
+impl NbtPath {
+    /// See [`NbtField::query`].
+    pub(crate) fn select<'a>(&self, root: &'a NbtField) -> Vec<Cow<'a, NbtValue>> {
+        let mut current = vec![Cow::Borrowed(&root.value)];
+        for node in &self.nodes {
+            let mut next = Vec::new();
+            for value in current {
+                match value {
+                    Cow::Borrowed(value) => node.select(value, &mut next),
+                    Cow::Owned(value) => {
+                        let mut selected = Vec::new();
+                        node.select(&value, &mut selected);
+                        next.extend(selected.into_iter().map(|v| Cow::Owned(v.into_owned())));
+                    }
+                }
+            }
+            current = next;
+        }
+        current
+    }
+
+    /// See [`NbtField::query_mut`].
+    pub(crate) fn select_mut<'a>(&self, root: &'a mut NbtField) -> Vec<NbtValueMut<'a>> {
+        let mut current = vec![NbtValueMut::Value(&mut root.value)];
+        for node in &self.nodes {
+            let mut next = Vec::new();
+            for value in current {
+                node.select_mut(value, &mut next);
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+impl Node {
+    fn select<'a>(&self, value: &'a NbtValue, out: &mut Vec<Cow<'a, NbtValue>>) {
+        match self {
+            Node::Root(pattern) => {
+                if matches_compound(pattern, value) {
+                    out.push(Cow::Borrowed(value));
+                }
+            }
+            Node::Child(name, pattern) => {
+                if let Some(field) = value.get(name) {
+                    if pattern
+                        .as_ref()
+                        .is_none_or(|p| matches_compound(p, &field.value))
+                    {
+                        out.push(Cow::Borrowed(&field.value));
+                    }
+                }
+            }
+            Node::Index(index) => {
+                if let Some(i) = resolve(*index, elements(value).len()) {
+                    out.extend(elements_from(value, i).next());
+                }
+            }
+            Node::All => out.extend(elements(value)),
+            Node::Matching(pattern) => {
+                out.extend(elements(value).filter(|e| matches_compound(pattern, e)));
+            }
+        }
+    }
+
+    fn select_mut<'a>(&self, target: NbtValueMut<'a>, out: &mut Vec<NbtValueMut<'a>>) {
+        match (self, target) {
+            (Node::Root(pattern), NbtValueMut::Value(value))
+                if matches_compound(pattern, value) =>
+            {
+                out.push(NbtValueMut::Value(value));
+            }
+            (
+                Node::Child(name, pattern),
+                NbtValueMut::Value(value) | NbtValueMut::Element(_, value),
+            ) => {
+                if let Some(child) = value.get_mut(name) {
+                    if pattern.as_ref().is_none_or(|p| matches_compound(p, child)) {
+                        out.push(NbtValueMut::Value(child));
+                    }
+                }
+            }
+            (Node::Index(index), target) => {
+                let mut elements = elements_mut(target);
+                if let Some(i) = resolve(*index, elements.len()) {
+                    out.extend(elements.nth(i));
+                }
+            }
+            (Node::All, target) => out.extend(elements_mut(target)),
+            (Node::Matching(pattern), target) => {
+                out.extend(elements_mut(target).filter(
+                    |e| matches!(e, NbtValueMut::Element(_, value) if matches_compound(pattern, value)),
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+// ---- NbtValueMut --------------------------------------------------------------------------------
+impl NbtValueMut<'_> {
+    /// The tag id of the referenced value.
+    pub fn tag(&self) -> u8 {
+        match self {
+            NbtValueMut::Value(v) => v.tag(),
+            NbtValueMut::Element(tag, _) => *tag,
+            NbtValueMut::Byte(_) | NbtValueMut::Boolean(_) => TAG_BYTE,
+            NbtValueMut::Short(_) => TAG_SHORT,
+            NbtValueMut::Int(_) => TAG_INT,
+            NbtValueMut::Long(_) => TAG_LONG,
+            NbtValueMut::Float(_) => TAG_FLOAT,
+            NbtValueMut::Double(_) => TAG_DOUBLE,
+            NbtValueMut::String(_) => TAG_STRING,
+            NbtValueMut::ByteArray(_) => TAG_BYTE_ARRAY,
+            NbtValueMut::IntArray(_) => TAG_INT_ARRAY,
+            NbtValueMut::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    /// A copy of the referenced value.
+    pub fn to_value(&self) -> NbtValue {
+        match self {
+            NbtValueMut::Value(v) | NbtValueMut::Element(_, v) => (**v).clone(),
+            NbtValueMut::Byte(b) => NbtValue::Byte(**b),
+            NbtValueMut::Boolean(b) => NbtValue::Boolean(**b),
+            NbtValueMut::Short(s) => NbtValue::Short(**s),
+            NbtValueMut::Int(i) => NbtValue::Int(**i),
+            NbtValueMut::Long(l) => NbtValue::Long(**l),
+            NbtValueMut::Float(f) => NbtValue::Float(**f),
+            NbtValueMut::Double(d) => NbtValue::Double(**d),
+            NbtValueMut::String(s) => NbtValue::String((**s).clone()),
+            NbtValueMut::ByteArray(a) => NbtValue::ByteArray((**a).clone()),
+            NbtValueMut::IntArray(a) => NbtValue::IntArray((**a).clone()),
+            NbtValueMut::LongArray(a) => NbtValue::LongArray((**a).clone()),
+        }
+    }
+
+    /// Replaces the referenced value. List and array elements only accept values of the type of
+    /// their list.
+    pub fn set(&mut self, value: NbtValue) -> Result<(), NbtError> {
+        let tag = value.tag();
+        match (self, value) {
+            (NbtValueMut::Value(v), value) => **v = value,
+            (NbtValueMut::Element(expected, v), value) if *expected == tag => **v = value,
+            (NbtValueMut::Byte(b), NbtValue::Byte(v)) => **b = v,
+            (NbtValueMut::Byte(b), NbtValue::Boolean(v)) => **b = v as u8,
+            (NbtValueMut::Boolean(b), NbtValue::Boolean(v)) => **b = v,
+            (NbtValueMut::Boolean(b), NbtValue::Byte(v @ (0 | 1))) => **b = v == 1,
+            (NbtValueMut::Short(s), NbtValue::Short(v)) => **s = v,
+            (NbtValueMut::Int(i), NbtValue::Int(v)) => **i = v,
+            (NbtValueMut::Long(l), NbtValue::Long(v)) => **l = v,
+            (NbtValueMut::Float(f), NbtValue::Float(v)) => **f = v,
+            (NbtValueMut::Double(d), NbtValue::Double(v)) => **d = v,
+            (NbtValueMut::String(s), NbtValue::String(v)) => **s = v,
+            (NbtValueMut::ByteArray(a), NbtValue::ByteArray(v)) => **a = v,
+            (NbtValueMut::IntArray(a), NbtValue::IntArray(v)) => **a = v,
+            (NbtValueMut::LongArray(a), NbtValue::LongArray(v)) => **a = v,
+            (target, _) => {
+                return Err(NbtError::InvalidWrite(format!(
+                    "cannot store a value of tag {} in an element of tag {}",
+                    tag,
+                    target.tag()
+                )))
+            }
+        }
+        Ok(())
+    }
+}
+
+// ---- Display ------------------------------------------------------------------------------------
+impl fmt::Display for NbtPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, node) in self.nodes.iter().enumerate() {
+            match node {
+                Node::Root(pattern) => write_pattern(f, pattern)?,
+                Node::Child(name, pattern) => {
+                    if i > 0 {
+                        f.write_char('.')?;
+                    }
+                    if !name.is_empty() && name.chars().all(is_name_char) {
+                        f.write_str(name)?;
+                    } else {
+                        write_quoted(f, name)?;
+                    }
+                    if let Some(pattern) = pattern {
+                        write_pattern(f, pattern)?;
+                    }
+                }
+                Node::Index(index) => write!(f, "[{}]", index)?,
+                Node::All => f.write_str("[]")?,
+                Node::Matching(pattern) => {
+                    f.write_char('[')?;
+                    write_pattern(f, pattern)?;
+                    f.write_char(']')?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ---- Helper functions ---------------------------------------------------------------------------
+fn is_name_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '"' | '\'' | '[' | ']' | '{' | '}' | '.')
+}
+
+fn write_pattern(f: &mut fmt::Formatter<'_>, pattern: &NbtCompound) -> fmt::Result {
+    SnbtWriter::new(f, false).write_compound(pattern)
+}
+
+/// The position of `index` in a sequence of `len` elements, counting from the end if negative.
+fn resolve(index: i32, len: usize) -> Option<usize> {
+    let i = if index < 0 {
+        len.checked_sub(index.unsigned_abs() as usize)?
+    } else {
+        index as usize
+    };
+    (i < len).then_some(i)
+}
+
+fn matches_compound(pattern: &NbtCompound, value: &NbtValue) -> bool {
+    match value {
+        NbtValue::Compound(fields) => pattern.iter().all(|p| {
+            fields
+                .get(&p.name)
+                .is_some_and(|f| matches(&p.value, &f.value))
+        }),
+        _ => false,
+    }
+}
+
+fn matches(pattern: &NbtValue, value: &NbtValue) -> bool {
+    match (pattern, value) {
+        (NbtValue::Compound(pattern), value) => matches_compound(pattern, value),
+        (NbtValue::List(_), NbtValue::List(_)) => {
+            let mut pattern = elements(pattern);
+            if pattern.len() == 0 {
+                return elements(value).len() == 0;
+            }
+            pattern.all(|p| elements(value).any(|v| matches(&p, &v)))
+        }
+        _ => pattern == value,
+    }
+}
+
+type Elements<'a, T> = Box<dyn ExactSizeIterator<Item = T> + 'a>;
+
+/// The elements of a list or array. Only elements of lists of lists and compounds are borrowed.
+pub(crate) fn elements(value: &NbtValue) -> Elements<'_, Cow<'_, NbtValue>> {
+    elements_from(value, 0)
+}
+
+/// Like [`elements`], starting at index `start`, which must not exceed the length.
+fn elements_from(value: &NbtValue, start: usize) -> Elements<'_, Cow<'_, NbtValue>> {
+    macro_rules! copied {
+        ($v:expr, $variant:ident) => {
+            Box::new(
+                $v[start..]
+                    .iter()
+                    .map(|e| Cow::Owned(NbtValue::$variant(e.clone()))),
+            )
+        };
+    }
+    match value {
+        NbtValue::List(list) => match list {
+            NbtList::Byte(v) => copied!(v, Byte),
+            NbtList::Boolean(v) => copied!(v, Boolean),
+            NbtList::Short(v) => copied!(v, Short),
+            NbtList::Int(v) => copied!(v, Int),
+            NbtList::Long(v) => copied!(v, Long),
+            NbtList::Float(v) => copied!(v, Float),
+            NbtList::Double(v) => copied!(v, Double),
+            NbtList::String(v) => copied!(v, String),
+            NbtList::List(v) => Box::new(v[start..].iter().map(Cow::Borrowed)),
+            NbtList::Compound(v) => Box::new(v[start..].iter().map(|f| Cow::Borrowed(&f.value))),
+            NbtList::ByteArray(v) => copied!(v, ByteArray),
+            NbtList::IntArray(v) => copied!(v, IntArray),
+            NbtList::LongArray(v) => copied!(v, LongArray),
+            NbtList::End => Box::new(std::iter::empty()),
+        },
+        NbtValue::ByteArray(v) => copied!(v, Byte),
+        NbtValue::IntArray(v) => copied!(v, Int),
+        NbtValue::LongArray(v) => copied!(v, Long),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+fn elements_mut(target: NbtValueMut<'_>) -> Elements<'_, NbtValueMut<'_>> {
+    macro_rules! referenced {
+        ($v:expr, $variant:ident) => {
+            Box::new($v.iter_mut().map(NbtValueMut::$variant))
+        };
+    }
+    match target {
+        NbtValueMut::Value(NbtValue::List(list))
+        | NbtValueMut::Element(_, NbtValue::List(list)) => match list {
+            NbtList::Byte(v) => referenced!(v, Byte),
+            NbtList::Boolean(v) => referenced!(v, Boolean),
+            NbtList::Short(v) => referenced!(v, Short),
+            NbtList::Int(v) => referenced!(v, Int),
+            NbtList::Long(v) => referenced!(v, Long),
+            NbtList::Float(v) => referenced!(v, Float),
+            NbtList::Double(v) => referenced!(v, Double),
+            NbtList::String(v) => referenced!(v, String),
+            NbtList::List(v) => Box::new(v.iter_mut().map(|e| NbtValueMut::Element(TAG_LIST, e))),
+            NbtList::Compound(v) => Box::new(
+                v.iter_mut()
+                    .map(|f| NbtValueMut::Element(TAG_COMPOUND, &mut f.value)),
+            ),
+            NbtList::ByteArray(v) => referenced!(v, ByteArray),
+            NbtList::IntArray(v) => referenced!(v, IntArray),
+            NbtList::LongArray(v) => referenced!(v, LongArray),
+            NbtList::End => Box::new(std::iter::empty()),
+        },
+        NbtValueMut::Value(NbtValue::ByteArray(v)) | NbtValueMut::ByteArray(v) => {
+            referenced!(v, Byte)
+        }
+        NbtValueMut::Value(NbtValue::IntArray(v)) | NbtValueMut::IntArray(v) => referenced!(v, Int),
+        NbtValueMut::Value(NbtValue::LongArray(v)) | NbtValueMut::LongArray(v) => {
+            referenced!(v, Long)
+        }
+        _ => Box::new(std::iter::empty()),
+    }
+}
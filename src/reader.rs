@@ -0,0 +1,320 @@
+use std::io::Read;
+
+use byteorder::ReadBytesExt;
+
+use crate::decode::{Decoder, ReadOptions};
+use crate::encode::Encoding;
+use crate::error::NbtError;
+use crate::value::*;
+
+/// An event produced by [`NbtReader`].
+///
+/// Elements of a list are reported like fields with an empty name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtEvent {
+    BeginCompound(String),
+    BeginList {
+        name: String,
+        tag: u8,
+        len: usize,
+    },
+    /// Any field that is not a compound or a list: numbers, strings and arrays.
+    Field(String, NbtValue),
+    /// Closes the innermost compound or list.
+    End,
+}
+
+/// Where a compound or list sits in its parent, used for error paths.
+#[derive(Clone)]
+enum Key {
+    Root,
+    Name(String),
+    Index(usize),
+}
+
+enum Frame {
+    Compound {
+        key: Key,
+    },
+    List {
+        key: Key,
+        tag: u8,
+        len: usize,
+        remaining: usize,
+    },
+}
+
+/// A pull parser that reads an uncompressed NBT stream one event at a time.
+///
+/// Only the value of the current field is held in memory, so large files can be scanned or
+/// partially read without building the whole tree. Iterating yields the events of the root field.
+pub struct NbtReader<R> {
+    decoder: Decoder<R>,
+    stack: Vec<Frame>,
+    started: bool,
+    done: bool,
+}
+
+// ---- Constructors -------------------------------------------------------------------------------
+impl<R: Read> NbtReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, &ReadOptions::default())
+    }
+
+    /// Creates a reader that enforces the given limits; allocation is counted over all events.
+    pub fn with_options(reader: R, options: &ReadOptions) -> Self {
+        NbtReader {
+            decoder: Decoder::new(reader, options),
+            stack: Vec::new(),
+            started: false,
+            done: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.decoder.into_inner()
+    }
+
+    /// The number of compounds and lists that are currently open.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+}
+
+// ---- Reading ------------------------------------------------------------------------------------
+impl<R: Read> NbtReader<R> {
+    /// Reads the next event, or `None` once the root field has been read completely.
+    pub fn next_event(&mut self) -> Result<Option<NbtEvent>, NbtError> {
+        if self.done {
+            return Ok(None);
+        }
+        let event = match self.next_header() {
+            Ok(Some((tag, name))) => self.begin(tag, name).map(Some),
+            Ok(None) => Ok(self.end()),
+            Err(e) => Err(e),
+        };
+        event.map_err(|e| self.locate(e))
+    }
+
+    /// Skips the rest of the innermost open compound or list, including its closing
+    /// [`NbtEvent::End`], without decoding the values inside it.
+    pub fn skip_container(&mut self) -> Result<(), NbtError> {
+        if let Some(&Frame::List { tag, remaining, .. }) = self.stack.last() {
+            if let Some(size) = self.fixed_size(tag) {
+                self.discard(remaining as u64 * size)
+                    .map_err(|e| self.locate(e))?;
+                if let Some(Frame::List { remaining, .. }) = self.stack.last_mut() {
+                    *remaining = 0;
+                }
+            }
+        }
+        let depth = self.stack.len();
+        while self.stack.len() >= depth && !self.done {
+            let skipped = match self.next_header() {
+                Ok(Some((tag, _))) => self.skip_payload(tag),
+                Ok(None) => {
+                    self.end();
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            };
+            skipped.map_err(|e| self.locate(e))?;
+        }
+        Ok(())
+    }
+
+    /// Reads the tag and name of the next field in the current container, or `None` if the
+    /// container (or the stream, for an empty root) is exhausted.
+    fn next_header(&mut self) -> Result<Option<(u8, String)>, NbtError> {
+        match self.stack.last_mut() {
+            None if self.started => Ok(None),
+            Some(Frame::List { remaining: 0, .. }) => Ok(None),
+            Some(Frame::List { tag, remaining, .. }) => {
+                *remaining -= 1;
+                Ok(Some((*tag, String::new())))
+            }
+            Some(Frame::Compound { .. }) => self.decoder.read_header(),
+            None => {
+                self.started = true;
+                let header = self.decoder.read_header()?;
+                let tag = header.as_ref().map_or(TAG_END, |(tag, _)| *tag);
+                self.decoder.check_root(tag)?;
+                Ok(header)
+            }
+        }
+    }
+
+    fn end(&mut self) -> Option<NbtEvent> {
+        let closed = self.stack.pop().is_some();
+        if closed {
+            self.decoder.leave();
+        }
+        if self.stack.is_empty() {
+            self.done = true;
+        }
+        closed.then_some(NbtEvent::End)
+    }
+
+    fn begin(&mut self, tag: u8, name: String) -> Result<NbtEvent, NbtError> {
+        let key = match self.stack.last() {
+            None => Key::Root,
+            Some(Frame::Compound { .. }) => Key::Name(name.clone()),
+            Some(Frame::List { len, remaining, .. }) => Key::Index(len - remaining - 1),
+        };
+        self.open(tag, name, key.clone())
+            .map_err(|e| key.apply(self.decoder.locate(e, tag)))
+    }
+
+    fn open(&mut self, tag: u8, name: String, key: Key) -> Result<NbtEvent, NbtError> {
+        match tag {
+            TAG_COMPOUND => {
+                self.decoder.enter()?;
+                self.stack.push(Frame::Compound { key });
+                Ok(NbtEvent::BeginCompound(name))
+            }
+            TAG_LIST => {
+                let element_tag = self.decoder.reader().read_u8()?;
+                let len = self.decoder.read_len()?;
+                check_list_type(element_tag, len)?;
+                self.decoder.enter()?;
+                self.stack.push(Frame::List {
+                    key,
+                    tag: element_tag,
+                    len,
+                    remaining: len,
+                });
+                Ok(NbtEvent::BeginList {
+                    name,
+                    tag: element_tag,
+                    len,
+                })
+            }
+            _ => Ok(NbtEvent::Field(name, self.decoder.read_payload(tag)?)),
+        }
+    }
+
+    /// Adds the offset and the path of the open compounds and lists to an error.
+    fn locate(&self, e: NbtError) -> NbtError {
+        let tag = match self.stack.last() {
+            None => TAG_END,
+            Some(Frame::Compound { .. }) => TAG_COMPOUND,
+            Some(Frame::List { .. }) => TAG_LIST,
+        };
+        let e = self.decoder.locate(e, tag);
+        self.stack.iter().rev().fold(e, |e, frame| match frame {
+            Frame::Compound { key } | Frame::List { key, .. } => key.apply(e),
+        })
+    }
+
+    /// Discards the payload of a `tag`, recursing into lists and compounds.
+    fn skip_payload(&mut self, tag: u8) -> Result<(), NbtError> {
+        if let Some(size) = self.fixed_size(tag) {
+            return self.discard(size);
+        }
+        match tag {
+            // only VarInts get here
+            TAG_INT => self.decoder.read_i32().map(drop),
+            TAG_LONG => self.decoder.read_i64().map(drop),
+            TAG_STRING => {
+                let len = self.decoder.read_string_len()?;
+                self.discard(len as u64)
+            }
+            TAG_BYTE_ARRAY => {
+                let len = self.decoder.read_len()?;
+                self.discard(len as u64)
+            }
+            TAG_INT_ARRAY => {
+                let len = self.decoder.read_len()?;
+                self.skip_elements(TAG_INT, len)
+            }
+            TAG_LONG_ARRAY => {
+                let len = self.decoder.read_len()?;
+                self.skip_elements(TAG_LONG, len)
+            }
+            TAG_LIST => {
+                let element_tag = self.decoder.reader().read_u8()?;
+                let len = self.decoder.read_len()?;
+                check_list_type(element_tag, len)?;
+                self.decoder.enter()?;
+                self.skip_elements(element_tag, len)?;
+                self.decoder.leave();
+                Ok(())
+            }
+            TAG_COMPOUND => {
+                self.decoder.enter()?;
+                loop {
+                    let tag = self.decoder.reader().read_u8()?;
+                    if tag == TAG_END {
+                        break;
+                    }
+                    let len = self.decoder.read_string_len()?;
+                    self.discard(len as u64)?;
+                    self.skip_payload(tag)?;
+                }
+                self.decoder.leave();
+                Ok(())
+            }
+            _ => Err(NbtError::UnknownTag(tag)),
+        }
+    }
+
+    /// Discards `len` payloads of a `tag`, all at once if they have a fixed size.
+    fn skip_elements(&mut self, tag: u8, len: usize) -> Result<(), NbtError> {
+        match self.fixed_size(tag) {
+            Some(size) => self.discard(len as u64 * size),
+            None => (0..len).try_for_each(|_| self.skip_payload(tag)),
+        }
+    }
+
+    /// The encoded size of a number, or `None` for VarInts and other tags.
+    fn fixed_size(&self, tag: u8) -> Option<u64> {
+        let varints = self.decoder.encoding() == Encoding::NetworkLittleEndian;
+        match tag {
+            TAG_BYTE => Some(1),
+            TAG_SHORT => Some(2),
+            TAG_INT | TAG_LONG if varints => None,
+            TAG_INT | TAG_FLOAT => Some(4),
+            TAG_LONG | TAG_DOUBLE => Some(8),
+            _ => None,
+        }
+    }
+
+    fn discard(&mut self, len: u64) -> Result<(), NbtError> {
+        let skipped = std::io::copy(&mut self.decoder.reader().take(len), &mut std::io::sink())?;
+        if skipped != len {
+            return Err(NbtError::UnexpectedEof);
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for NbtReader<R> {
+    type Item = Result<NbtEvent, NbtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.next_event();
+        if event.is_err() {
+            self.done = true;
+        }
+        event.transpose()
+    }
+}
+
+// ---- Helper functions ---------------------------------------------------------------------------
+impl Key {
+    fn apply(&self, e: NbtError) -> NbtError {
+        match self {
+            Key::Root => e,
+            Key::Name(name) => e.in_field(name),
+            Key::Index(index) => e.in_element(*index),
+        }
+    }
+}
+
+fn check_list_type(tag: u8, len: usize) -> Result<(), NbtError> {
+    match tag {
+        TAG_END if len > 0 => Err(NbtError::InvalidListType(tag)),
+        TAG_END..=TAG_LONG_ARRAY => Ok(()),
+        _ => Err(NbtError::InvalidListType(tag)),
+    }
+}
@@ -0,0 +1,380 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::compression::{decoder, encode, Compression};
+use crate::error::NbtError;
+use crate::field::NbtField;
+use crate::write_nbt;
+
+const SECTOR_SIZE: usize = 4096;
+const REGION_CHUNKS: usize = 32;
+
+const HEADER_ENTRIES: usize = REGION_CHUNKS * REGION_CHUNKS;
+const HEADER_SECTORS: u32 = 2;
+const MAX_CHUNK_SECTORS: usize = 255;
+
+const CHUNK_GZIP: u8 = 1;
+const CHUNK_ZLIB: u8 = 2;
+const CHUNK_UNCOMPRESSED: u8 = 3;
+const CHUNK_LZ4: u8 = 4;
+/// Set on the compression byte when the chunk data lives in a separate `c.X.Z.mcc` file.
+const CHUNK_EXTERNAL: u8 = 0x80;
+
+/// An Anvil region file (`r.X.Z.mca`) holding up to 32x32 chunks.
+///
+/// Chunk coordinates may be given either relative to the region (0..32) or as absolute chunk
+/// coordinates, only the lowest five bits are used.
+///
+/// Chunks too large for the region file are stored next to it as `c.X.Z.mcc` files. This is only
+/// possible if the region knows its directory and position, see [`Region::set_external_dir`].
+pub struct Region<S> {
+    stream: S,
+    locations: [u32; HEADER_ENTRIES],
+    timestamps: [u32; HEADER_ENTRIES],
+    external: Option<ExternalDir>,
+}
+
+struct ExternalDir {
+    dir: PathBuf,
+    region_x: i32,
+    region_z: i32,
+}
+
+// ---- Constructors -------------------------------------------------------------------------------
+impl Region<File> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Region<File>, NbtError> {
+        let mut region = Region::new(File::open(path.as_ref())?)?;
+        region.detect_external_dir(path.as_ref());
+        Ok(region)
+    }
+
+    /// Opens a region file for reading and writing, creating it if it does not exist yet.
+    pub fn open_or_create<P: AsRef<Path>>(path: P) -> Result<Region<File>, NbtError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path.as_ref())?;
+        let mut region = Region::new(file)?;
+        region.detect_external_dir(path.as_ref());
+        Ok(region)
+    }
+
+    /// Derives the directory and region position for `.mcc` files from an `r.X.Z.mca` path.
+    fn detect_external_dir(&mut self, path: &Path) {
+        let position = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(parse_region_name);
+        if let Some((region_x, region_z)) = position {
+            let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            self.set_external_dir(dir, region_x, region_z);
+        }
+    }
+}
+
+impl<S: Read + Seek> Region<S> {
+    pub fn new(mut stream: S) -> Result<Region<S>, NbtError> {
+        let mut locations = [0; HEADER_ENTRIES];
+        let mut timestamps = [0; HEADER_ENTRIES];
+
+        // a freshly created region file may still be empty
+        if stream.seek(SeekFrom::End(0))? > 0 {
+            stream.seek(SeekFrom::Start(0))?;
+            stream.read_u32_into::<BigEndian>(&mut locations)?;
+            stream.read_u32_into::<BigEndian>(&mut timestamps)?;
+        }
+
+        Ok(Region {
+            stream,
+            locations,
+            timestamps,
+            external: None,
+        })
+    }
+}
+
+// ---- Header Access ------------------------------------------------------------------------------
+impl<S> Region<S> {
+    pub fn has_chunk(&self, x: i32, z: i32) -> bool {
+        self.locations[chunk_index(x, z)] != 0
+    }
+
+    /// Last modification time of the chunk in seconds since the epoch.
+    pub fn timestamp(&self, x: i32, z: i32) -> Option<u32> {
+        let index = chunk_index(x, z);
+        if self.locations[index] == 0 {
+            return None;
+        }
+        Some(self.timestamps[index])
+    }
+
+    /// Local coordinates of all chunks present in this region.
+    pub fn chunk_positions(&self) -> Vec<(i32, i32)> {
+        (0..HEADER_ENTRIES)
+            .filter(|&i| self.locations[i] != 0)
+            .map(|i| ((i % REGION_CHUNKS) as i32, (i / REGION_CHUNKS) as i32))
+            .collect()
+    }
+
+    /// Sets where oversized chunks of this region are stored. `region_x` and `region_z` are the
+    /// region coordinates, i.e. those in the file name `r.X.Z.mca`.
+    pub fn set_external_dir<P: Into<PathBuf>>(&mut self, dir: P, region_x: i32, region_z: i32) {
+        self.external = Some(ExternalDir {
+            dir: dir.into(),
+            region_x,
+            region_z,
+        });
+    }
+
+    fn external_path(&self, x: i32, z: i32) -> Result<PathBuf, NbtError> {
+        let external = self
+            .external
+            .as_ref()
+            .ok_or(NbtError::ExternalChunkUnavailable(x, z))?;
+        let chunk_x = external.region_x * REGION_CHUNKS as i32 + (x & (REGION_CHUNKS as i32 - 1));
+        let chunk_z = external.region_z * REGION_CHUNKS as i32 + (z & (REGION_CHUNKS as i32 - 1));
+        Ok(external.dir.join(format!("c.{}.{}.mcc", chunk_x, chunk_z)))
+    }
+
+    fn remove_external(&self, x: i32, z: i32) -> Result<(), NbtError> {
+        if let Ok(path) = self.external_path(x, z) {
+            match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+// ---- Chunk Access -------------------------------------------------------------------------------
+impl<S: Read + Seek> Region<S> {
+    pub fn read_chunk(&mut self, x: i32, z: i32) -> Result<Option<NbtField>, NbtError> {
+        let location = self.locations[chunk_index(x, z)];
+        if location == 0 {
+            return Ok(None);
+        }
+        if location >> 8 < HEADER_SECTORS {
+            return Err(NbtError::CorruptChunk(format!(
+                "chunk ({}, {}) starts at sector {} inside the header",
+                x,
+                z,
+                location >> 8
+            )));
+        }
+        let offset = (location >> 8) as u64 * SECTOR_SIZE as u64;
+        let sectors = (location & 0xff) as usize;
+
+        self.stream.seek(SeekFrom::Start(offset))?;
+        let len = self.stream.read_u32::<BigEndian>()? as usize;
+        if len == 0 || len + 4 > sectors * SECTOR_SIZE {
+            return Err(NbtError::CorruptChunk(format!(
+                "chunk ({}, {}) has length {} but only {} sectors",
+                x, z, len, sectors
+            )));
+        }
+        let compression = self.stream.read_u8()?;
+        let payload = if compression & CHUNK_EXTERNAL != 0 {
+            std::fs::read(self.external_path(x, z)?)?
+        } else {
+            let mut payload = vec![0; len - 1];
+            self.stream.read_exact(&mut payload)?;
+            payload
+        };
+
+        let compression = chunk_compression(compression & !CHUNK_EXTERNAL)?;
+        let mut r = decoder(Cursor::new(payload), compression);
+        Ok(Some(NbtField::read(&mut r)?))
+    }
+
+    /// Iterates over all chunks present in this region in header order.
+    pub fn chunks(&mut self) -> Chunks<'_, S> {
+        Chunks {
+            positions: self.chunk_positions().into_iter(),
+            region: self,
+        }
+    }
+}
+
+impl<S: Read + Write + Seek> Region<S> {
+    /// Writes `chunk` at the given position, stamping it with the current time.
+    ///
+    /// The chunk keeps its sectors if the new data still fits, otherwise it is moved to the first
+    /// free run of sectors. No other chunk is touched. Chunks larger than 255 sectors (about
+    /// 1 MiB) are written to an external `.mcc` file.
+    pub fn write_chunk(
+        &mut self,
+        x: i32,
+        z: i32,
+        chunk: &NbtField,
+        compression: Compression,
+    ) -> Result<(), NbtError> {
+        let index = chunk_index(x, z);
+        let mut data = Vec::new();
+        encode(&mut data, compression, |mut w| write_nbt(&mut w, chunk))?;
+
+        let mut compression_id = chunk_compression_id(compression);
+        if (data.len() + 5).div_ceil(SECTOR_SIZE) > MAX_CHUNK_SECTORS {
+            let path = match self.external_path(x, z) {
+                Ok(path) => path,
+                Err(_) => return Err(NbtError::ChunkTooLarge(data.len() + 5)),
+            };
+            std::fs::write(path, &data)?;
+            data.clear();
+            compression_id |= CHUNK_EXTERNAL;
+        } else {
+            self.remove_external(x, z)?;
+        }
+
+        let len = data.len() + 5;
+        let sectors = len.div_ceil(SECTOR_SIZE);
+
+        let old = self.locations[index];
+        let offset = if old >> 8 >= HEADER_SECTORS && sectors as u32 <= old & 0xff {
+            old >> 8
+        } else {
+            self.locations[index] = 0;
+            let offset = self.allocate(sectors as u32);
+            self.locations[index] = old;
+            offset
+        };
+
+        self.ensure_header()?;
+        self.stream
+            .seek(SeekFrom::Start(offset as u64 * SECTOR_SIZE as u64))?;
+        self.stream.write_u32::<BigEndian>(data.len() as u32 + 1)?;
+        self.stream.write_u8(compression_id)?;
+        self.stream.write_all(&data)?;
+        self.stream
+            .write_all(&[0; SECTOR_SIZE][..sectors * SECTOR_SIZE - len])?;
+
+        self.locations[index] = (offset << 8) | sectors as u32;
+        self.timestamps[index] = now();
+        self.write_header_entry(index)
+    }
+
+    /// Removes the chunk from the header, its sectors become free for reuse.
+    pub fn remove_chunk(&mut self, x: i32, z: i32) -> Result<(), NbtError> {
+        let index = chunk_index(x, z);
+        if self.locations[index] == 0 {
+            return Ok(());
+        }
+        self.locations[index] = 0;
+        self.timestamps[index] = 0;
+        self.write_header_entry(index)?;
+        self.remove_external(x, z)
+    }
+
+    pub fn flush(&mut self) -> Result<(), NbtError> {
+        Ok(self.stream.flush()?)
+    }
+
+    /// First-fit search for `count` consecutive sectors not used by any chunk. Corrupt entries
+    /// that point into the header are ignored.
+    fn allocate(&self, count: u32) -> u32 {
+        let mut used: Vec<(u32, u32)> = self
+            .locations
+            .iter()
+            .filter(|&&l| l >> 8 >= HEADER_SECTORS)
+            .map(|l| (l >> 8, l & 0xff))
+            .collect();
+        used.sort_unstable();
+
+        let mut start = HEADER_SECTORS;
+        for (offset, sectors) in used {
+            if offset >= start + count {
+                break;
+            }
+            start = start.max(offset + sectors);
+        }
+        start
+    }
+
+    fn ensure_header(&mut self) -> Result<(), NbtError> {
+        if self.stream.seek(SeekFrom::End(0))? < HEADER_SECTORS as u64 * SECTOR_SIZE as u64 {
+            self.stream.seek(SeekFrom::Start(0))?;
+            self.stream
+                .write_all(&[0; HEADER_SECTORS as usize * SECTOR_SIZE])?;
+        }
+        Ok(())
+    }
+
+    fn write_header_entry(&mut self, index: usize) -> Result<(), NbtError> {
+        self.ensure_header()?;
+        self.stream.seek(SeekFrom::Start(index as u64 * 4))?;
+        self.stream.write_u32::<BigEndian>(self.locations[index])?;
+        self.stream
+            .seek(SeekFrom::Start((SECTOR_SIZE + index * 4) as u64))?;
+        self.stream.write_u32::<BigEndian>(self.timestamps[index])?;
+        Ok(())
+    }
+}
+
+pub struct Chunks<'a, S> {
+    region: &'a mut Region<S>,
+    positions: std::vec::IntoIter<(i32, i32)>,
+}
+
+impl<S: Read + Seek> Iterator for Chunks<'_, S> {
+    type Item = Result<(i32, i32, NbtField), NbtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, z) = self.positions.next()?;
+        Some(
+            self.region
+                .read_chunk(x, z)
+                .map(|chunk| (x, z, chunk.expect("chunk listed in header"))),
+        )
+    }
+}
+
+// ---- Helper functions ---------------------------------------------------------------------------
+fn chunk_index(x: i32, z: i32) -> usize {
+    (x as usize & (REGION_CHUNKS - 1)) + (z as usize & (REGION_CHUNKS - 1)) * REGION_CHUNKS
+}
+
+fn chunk_compression_id(compression: Compression) -> u8 {
+    match compression {
+        Compression::Gzip(_) => CHUNK_GZIP,
+        Compression::Zlib(_) => CHUNK_ZLIB,
+        Compression::None => CHUNK_UNCOMPRESSED,
+        Compression::Lz4 => CHUNK_LZ4,
+    }
+}
+
+fn parse_region_name(name: &str) -> Option<(i32, i32)> {
+    let mut parts = name.strip_prefix("r.")?.strip_suffix(".mca")?.split('.');
+    let x = parts.next()?.parse().ok()?;
+    let z = parts.next()?.parse().ok()?;
+    match parts.next() {
+        None => Some((x, z)),
+        Some(_) => None,
+    }
+}
+
+fn now() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as u32)
+        .unwrap_or(0)
+}
+
+fn chunk_compression(id: u8) -> Result<Compression, NbtError> {
+    match id {
+        CHUNK_GZIP => Ok(Compression::gzip()),
+        CHUNK_ZLIB => Ok(Compression::zlib()),
+        CHUNK_UNCOMPRESSED => Ok(Compression::None),
+        CHUNK_LZ4 => Ok(Compression::Lz4),
+        _ => Err(NbtError::UnknownChunkCompression(id)),
+    }
+}
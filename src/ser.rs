@@ -0,0 +1,392 @@
+use serde::ser::{self, Serialize};
+
+use crate::arrays::{BYTE_ARRAY_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN};
+use crate::compound::NbtCompound;
+use crate::error::NbtError;
+use crate::field::NbtField;
+use crate::list::NbtList;
+use crate::value::NbtValue;
+
+/// Converts any serializable value into an unnamed NBT field.
+///
+/// Structs and maps become compounds, sequences become lists and `bool` becomes a `Byte` of 0 or
+/// 1. `None` fields are left out. Use [`crate::IntArray`] and friends to get typed arrays.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<NbtField, NbtError> {
+    match value.serialize(ValueSerializer)? {
+        NbtValue::End => Err(ser::Error::custom("cannot serialize None as root")),
+        value => Ok(NbtField {
+            name: String::new(),
+            value,
+        }),
+    }
+}
+
+pub(crate) struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = NbtValue;
+    type Error = NbtError;
+
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = VariantSerializer<SeqSerializer>;
+    type SerializeMap = CompoundSerializer;
+    type SerializeStruct = CompoundSerializer;
+    type SerializeStructVariant = VariantSerializer<CompoundSerializer>;
+
+    fn serialize_bool(self, v: bool) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Byte(v as u8))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Byte(v as u8))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Short(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Int(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Long(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Byte(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Int(v as i32))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Long(v as i64))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<NbtValue, NbtError> {
+        i64::try_from(v)
+            .map(NbtValue::Long)
+            .map_err(|_| ser::Error::custom(format!("{} does not fit into a Long", v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Float(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Double(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::ByteArray(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::End)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<NbtValue, NbtError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Compound(NbtCompound::new()))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<NbtValue, NbtError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<NbtValue, NbtError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<NbtValue, NbtError> {
+        let value = value.serialize(self)?;
+        match name {
+            BYTE_ARRAY_TOKEN | INT_ARRAY_TOKEN | LONG_ARRAY_TOKEN => into_array(name, value),
+            _ => Ok(value),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Compound(NbtCompound::from([NbtField {
+            name: variant.to_string(),
+            value: value.serialize(self)?,
+        }])))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, NbtError> {
+        Ok(SeqSerializer {
+            values: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, NbtError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, NbtError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<SeqSerializer>, NbtError> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<CompoundSerializer, NbtError> {
+        Ok(CompoundSerializer {
+            fields: NbtCompound::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<CompoundSerializer, NbtError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<CompoundSerializer>, NbtError> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+fn into_array(token: &str, value: NbtValue) -> Result<NbtValue, NbtError> {
+    let list = match value {
+        NbtValue::List(list) => list,
+        _ => return Err(ser::Error::custom("typed arrays must be sequences")),
+    };
+    match (token, list) {
+        (BYTE_ARRAY_TOKEN, NbtList::Byte(v)) => Ok(NbtValue::ByteArray(v)),
+        (BYTE_ARRAY_TOKEN, NbtList::End) => Ok(NbtValue::ByteArray(Vec::new())),
+        (INT_ARRAY_TOKEN, NbtList::Int(v)) => Ok(NbtValue::IntArray(v)),
+        (INT_ARRAY_TOKEN, NbtList::End) => Ok(NbtValue::IntArray(Vec::new())),
+        (LONG_ARRAY_TOKEN, NbtList::Long(v)) => Ok(NbtValue::LongArray(v)),
+        (LONG_ARRAY_TOKEN, NbtList::End) => Ok(NbtValue::LongArray(Vec::new())),
+        _ => Err(ser::Error::custom(
+            "typed array elements have the wrong type",
+        )),
+    }
+}
+
+// ---- Compound types -----------------------------------------------------------------------------
+pub(crate) struct SeqSerializer {
+    values: Vec<NbtValue>,
+}
+
+impl SeqSerializer {
+    fn finish(self) -> Result<NbtValue, NbtError> {
+        NbtList::from_values(self.values)
+            .map(NbtValue::List)
+            .ok_or_else(|| ser::Error::custom("list elements must all have the same type"))
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = NbtValue;
+    type Error = NbtError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        match value.serialize(ValueSerializer)? {
+            NbtValue::End => Err(ser::Error::custom("lists cannot contain None")),
+            value => {
+                self.values.push(value);
+                Ok(())
+            }
+        }
+    }
+
+    fn end(self) -> Result<NbtValue, NbtError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = NbtValue;
+    type Error = NbtError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<NbtValue, NbtError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = NbtValue;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<NbtValue, NbtError> {
+        self.finish()
+    }
+}
+
+pub(crate) struct CompoundSerializer {
+    fields: NbtCompound,
+    key: Option<String>,
+}
+
+impl CompoundSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, name: String, value: &T) -> Result<(), NbtError> {
+        match value.serialize(ValueSerializer)? {
+            NbtValue::End => {}
+            value => {
+                self.fields.insert(NbtField { name, value });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for CompoundSerializer {
+    type Ok = NbtValue;
+    type Error = NbtError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), NbtError> {
+        self.key = Some(match key.serialize(ValueSerializer)? {
+            NbtValue::String(s) => s,
+            NbtValue::Byte(b) => (b as i8).to_string(),
+            NbtValue::Short(s) => s.to_string(),
+            NbtValue::Int(i) => i.to_string(),
+            NbtValue::Long(l) => l.to_string(),
+            _ => {
+                return Err(ser::Error::custom(
+                    "compound keys must be strings or integers",
+                ))
+            }
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        let key = self.key.take().ok_or_else(|| {
+            NbtError::SerdeError("serialize_value called before serialize_key".to_string())
+        })?;
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Compound(self.fields))
+    }
+}
+
+impl ser::SerializeStruct for CompoundSerializer {
+    type Ok = NbtValue;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), NbtError> {
+        self.push(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<NbtValue, NbtError> {
+        Ok(NbtValue::Compound(self.fields))
+    }
+}
+
+/// Wraps the content of tuple and struct variants into a single-entry compound `{variant: ...}`.
+pub(crate) struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl<S> VariantSerializer<S> {
+    fn wrap(variant: &str, value: NbtValue) -> NbtValue {
+        NbtValue::Compound(NbtCompound::from([NbtField {
+            name: variant.to_string(),
+            value,
+        }]))
+    }
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<SeqSerializer> {
+    type Ok = NbtValue;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<NbtValue, NbtError> {
+        Ok(Self::wrap(self.variant, self.inner.finish()?))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<CompoundSerializer> {
+    type Ok = NbtValue;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), NbtError> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<NbtValue, NbtError> {
+        let value = ser::SerializeStruct::end(self.inner)?;
+        Ok(Self::wrap(self.variant, value))
+    }
+}
@@ -0,0 +1,535 @@
+use std::fmt::{self, Write};
+
+use crate::compound::NbtCompound;
+use crate::error::NbtError;
+use crate::field::NbtField;
+use crate::list::NbtList;
+use crate::value::NbtValue;
+
+const MAX_DEPTH: usize = 512;
+const INDENT: &str = "    ";
+
+// ---- Parser -------------------------------------------------------------------------------------
+
+/// Parses stringified NBT as used by commands, e.g. `{Count:1b,id:"minecraft:stone"}`.
+pub fn parse_snbt(input: &str) -> Result<NbtValue, NbtError> {
+    let mut parser = SnbtParser { input, pos: 0 };
+    let value = parser.read_value(0)?;
+    parser.skip_whitespace();
+    if parser.pos < input.len() {
+        return Err(parser.error("trailing data after value"));
+    }
+    Ok(value)
+}
+
+/// Parses an SNBT compound that starts at byte `pos` of `input`, for syntax that embeds SNBT such
+/// as NBT paths. Returns the compound and the position after it.
+pub(crate) fn parse_compound_at(input: &str, pos: usize) -> Result<(NbtCompound, usize), NbtError> {
+    let mut parser = SnbtParser { input, pos };
+    let compound = parser.read_compound(0)?;
+    Ok((compound, parser.pos))
+}
+
+/// Like [`parse_compound_at`], for a quoted string.
+pub(crate) fn parse_quoted_at(input: &str, pos: usize) -> Result<(String, usize), NbtError> {
+    let mut parser = SnbtParser { input, pos };
+    let s = parser.read_quoted()?;
+    Ok((s, parser.pos))
+}
+
+struct SnbtParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl SnbtParser<'_> {
+    fn error<S: Into<String>>(&self, message: S) -> NbtError {
+        NbtError::SnbtParseError {
+            position: self.pos,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), NbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("expected '{}' but found '{}'", expected, c))),
+            None => Err(self.error(format!("expected '{}' but reached end of input", expected))),
+        }
+    }
+
+    /// Consumes `c` if it is the next non-whitespace character.
+    fn accept(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<NbtValue, NbtError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.read_compound(depth).map(NbtValue::Compound),
+            Some('[') => self.read_list_or_array(depth),
+            Some('"') | Some('\'') => self.read_quoted().map(NbtValue::String),
+            Some(_) => {
+                let start = self.pos;
+                let token = self.read_unquoted();
+                if token.is_empty() {
+                    return Err(self.error("expected value"));
+                }
+                Ok(parse_token(token).ok_or_else(|| NbtError::SnbtParseError {
+                    position: start,
+                    message: format!("invalid value '{}'", token),
+                })?)
+            }
+            None => Err(self.error("expected value but reached end of input")),
+        }
+    }
+
+    fn read_compound(&mut self, depth: usize) -> Result<NbtCompound, NbtError> {
+        self.expect('{')?;
+        let mut fields = NbtCompound::new();
+        while !self.accept('}') {
+            let name = self.read_key()?;
+            self.expect(':')?;
+            let value = self.read_value(depth + 1)?;
+            fields.insert(NbtField { name, value });
+            if !self.accept(',') {
+                self.expect('}')?;
+                break;
+            }
+        }
+        Ok(fields)
+    }
+
+    fn read_key(&mut self) -> Result<String, NbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') | Some('\'') => self.read_quoted(),
+            _ => {
+                let key = self.read_unquoted();
+                if key.is_empty() {
+                    return Err(self.error("expected key"));
+                }
+                Ok(key.to_string())
+            }
+        }
+    }
+
+    fn read_list_or_array(&mut self, depth: usize) -> Result<NbtValue, NbtError> {
+        self.expect('[')?;
+        let rest = &self.input[self.pos..];
+        let mut prefix = rest.chars();
+        if let (Some(kind @ ('B' | 'I' | 'L')), Some(';')) = (prefix.next(), prefix.next()) {
+            self.pos += 2;
+            return self.read_array(kind);
+        }
+
+        let start = self.pos;
+        let mut values = Vec::new();
+        while !self.accept(']') {
+            values.push(self.read_value(depth + 1)?);
+            if !self.accept(',') {
+                self.expect(']')?;
+                break;
+            }
+        }
+
+        NbtList::from_values(values)
+            .map(NbtValue::List)
+            .ok_or_else(|| NbtError::SnbtParseError {
+                position: start,
+                message: "list elements must all have the same type".to_string(),
+            })
+    }
+
+    fn read_array(&mut self, kind: char) -> Result<NbtValue, NbtError> {
+        let mut bytes = Vec::new();
+        let mut ints = Vec::new();
+        let mut longs = Vec::new();
+        while !self.accept(']') {
+            self.skip_whitespace();
+            let start = self.pos;
+            let token = self.read_unquoted();
+            let value = parse_token(token);
+            match (kind, value) {
+                ('B', Some(NbtValue::Byte(b))) => bytes.push(b),
+                ('B', Some(NbtValue::Boolean(b))) => bytes.push(b as u8),
+                ('I', Some(NbtValue::Int(i))) => ints.push(i),
+                ('L', Some(NbtValue::Long(l))) => longs.push(l),
+                _ => {
+                    return Err(NbtError::SnbtParseError {
+                        position: start,
+                        message: format!("invalid element '{}' in [{};] array", token, kind),
+                    })
+                }
+            }
+            if !self.accept(',') {
+                self.expect(']')?;
+                break;
+            }
+        }
+        Ok(match kind {
+            'B' => NbtValue::ByteArray(bytes),
+            'I' => NbtValue::IntArray(ints),
+            _ => NbtValue::LongArray(longs),
+        })
+    }
+
+    fn read_unquoted(&mut self) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(is_unquoted_char) {
+            self.pos += 1;
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn read_quoted(&mut self) -> Result<String, NbtError> {
+        let quote = self.next_char().unwrap_or('"');
+        let mut s = String::new();
+        loop {
+            match self.next_char() {
+                Some('\\') => {
+                    let escape_start = self.pos - 1;
+                    let c = match self.next_char() {
+                        Some(c) if c == quote || c == '\\' => c,
+                        Some('"') => '"',
+                        Some('\'') => '\'',
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('s') => ' ',
+                        Some('u') => self.read_unicode_escape(escape_start)?,
+                        _ => {
+                            return Err(NbtError::SnbtParseError {
+                                position: escape_start,
+                                message: "invalid escape sequence".to_string(),
+                            })
+                        }
+                    };
+                    s.push(c);
+                }
+                Some(c) if c == quote => return Ok(s),
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn read_unicode_escape(&mut self, escape_start: usize) -> Result<char, NbtError> {
+        let digits = self.input.get(self.pos..self.pos + 4);
+        let c = digits
+            .filter(|d| d.chars().all(|c| c.is_ascii_hexdigit()))
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .and_then(char::from_u32)
+            .ok_or(NbtError::SnbtParseError {
+                position: escape_start,
+                message: "invalid unicode escape".to_string(),
+            })?;
+        self.pos += 4;
+        Ok(c)
+    }
+}
+
+// ---- Writer -------------------------------------------------------------------------------------
+
+/// Writes values as SNBT that [`parse_snbt`] and Minecraft commands accept. The pretty mode puts
+/// every compound entry and every nested compound or list on its own line. Values without an SNBT
+/// form are written lossily, see [`NbtValue::to_snbt`].
+pub(crate) struct SnbtWriter<'w, W: Write> {
+    w: &'w mut W,
+    pretty: bool,
+    depth: usize,
+}
+
+impl<'w, W: Write> SnbtWriter<'w, W> {
+    pub(crate) fn new(w: &'w mut W, pretty: bool) -> Self {
+        SnbtWriter {
+            w,
+            pretty,
+            depth: 0,
+        }
+    }
+
+    pub(crate) fn write_value(&mut self, value: &NbtValue) -> fmt::Result {
+        match value {
+            NbtValue::Byte(b) => write!(self.w, "{}b", *b as i8),
+            NbtValue::Boolean(b) => write!(self.w, "{}", b),
+            NbtValue::Short(s) => write!(self.w, "{}s", s),
+            NbtValue::Int(i) => write!(self.w, "{}", i),
+            NbtValue::Long(l) => write!(self.w, "{}L", l),
+            NbtValue::Float(f) => write!(self.w, "{}f", f),
+            NbtValue::Double(d) => write!(self.w, "{}d", d),
+            NbtValue::String(s) => write_quoted(self.w, s),
+            NbtValue::List(l) => self.write_list(l),
+            NbtValue::Compound(c) => self.write_compound(c),
+            NbtValue::ByteArray(arr) => self.write_array("B", arr.iter().map(|b| *b as i8), "b"),
+            NbtValue::IntArray(arr) => self.write_array("I", arr.iter(), ""),
+            NbtValue::LongArray(arr) => self.write_array("L", arr.iter(), "L"),
+            NbtValue::End => Ok(()),
+        }
+    }
+
+    pub(crate) fn write_field(&mut self, field: &NbtField) -> fmt::Result {
+        write_key(self.w, &field.name)?;
+        self.w.write_str(if self.pretty { ": " } else { ":" })?;
+        self.write_value(&field.value)
+    }
+
+    pub(crate) fn write_compound(&mut self, fields: &[NbtField]) -> fmt::Result {
+        if fields.is_empty() {
+            return self.w.write_str("{}");
+        }
+        self.w.write_char('{')?;
+        self.depth += 1;
+        for (i, field) in fields.iter().enumerate() {
+            self.separator(i)?;
+            self.write_field(field)?;
+        }
+        self.depth -= 1;
+        self.newline()?;
+        self.w.write_char('}')
+    }
+
+    pub(crate) fn write_list(&mut self, list: &NbtList) -> fmt::Result {
+        match list {
+            NbtList::Byte(v) => self.write_scalars(v.iter().map(|b| NbtValue::Byte(*b))),
+            NbtList::Boolean(v) => self.write_scalars(v.iter().map(|b| NbtValue::Boolean(*b))),
+            NbtList::Short(v) => self.write_scalars(v.iter().map(|s| NbtValue::Short(*s))),
+            NbtList::Int(v) => self.write_scalars(v.iter().map(|i| NbtValue::Int(*i))),
+            NbtList::Long(v) => self.write_scalars(v.iter().map(|l| NbtValue::Long(*l))),
+            NbtList::Float(v) => self.write_scalars(v.iter().map(|f| NbtValue::Float(*f))),
+            NbtList::Double(v) => self.write_scalars(v.iter().map(|d| NbtValue::Double(*d))),
+            NbtList::String(v) => {
+                self.w.write_char('[')?;
+                for (i, s) in v.iter().enumerate() {
+                    self.inline_separator(i)?;
+                    write_quoted(self.w, s)?;
+                }
+                self.w.write_char(']')
+            }
+            NbtList::List(v) => self.write_nested(v.iter()),
+            NbtList::Compound(v) => self.write_nested(v.iter().map(|f| &f.value)),
+            NbtList::ByteArray(v) => self.write_nested_arrays(v, |w, arr| {
+                w.write_array("B", arr.iter().map(|b| *b as i8), "b")
+            }),
+            NbtList::IntArray(v) => {
+                self.write_nested_arrays(v, |w, arr| w.write_array("I", arr.iter(), ""))
+            }
+            NbtList::LongArray(v) => {
+                self.write_nested_arrays(v, |w, arr| w.write_array("L", arr.iter(), "L"))
+            }
+            NbtList::End => self.w.write_str("[]"),
+        }
+    }
+
+    fn write_scalars<I: Iterator<Item = NbtValue>>(&mut self, values: I) -> fmt::Result {
+        self.w.write_char('[')?;
+        for (i, value) in values.enumerate() {
+            self.inline_separator(i)?;
+            self.write_value(&value)?;
+        }
+        self.w.write_char(']')
+    }
+
+    fn write_nested<'a, I: Iterator<Item = &'a NbtValue>>(&mut self, values: I) -> fmt::Result {
+        self.w.write_char('[')?;
+        self.depth += 1;
+        let mut empty = true;
+        for (i, value) in values.enumerate() {
+            self.separator(i)?;
+            self.write_value(value)?;
+            empty = false;
+        }
+        self.depth -= 1;
+        if !empty {
+            self.newline()?;
+        }
+        self.w.write_char(']')
+    }
+
+    fn write_nested_arrays<T, F>(&mut self, arrays: &[T], mut write: F) -> fmt::Result
+    where
+        F: FnMut(&mut Self, &T) -> fmt::Result,
+    {
+        self.w.write_char('[')?;
+        self.depth += 1;
+        for (i, arr) in arrays.iter().enumerate() {
+            self.separator(i)?;
+            write(self, arr)?;
+        }
+        self.depth -= 1;
+        if !arrays.is_empty() {
+            self.newline()?;
+        }
+        self.w.write_char(']')
+    }
+
+    fn write_array<T: fmt::Display, I: Iterator<Item = T>>(
+        &mut self,
+        kind: &str,
+        values: I,
+        suffix: &str,
+    ) -> fmt::Result {
+        write!(self.w, "[{};", kind)?;
+        for (i, value) in values.enumerate() {
+            if i > 0 {
+                self.w.write_char(',')?;
+            }
+            if self.pretty {
+                self.w.write_char(' ')?;
+            }
+            write!(self.w, "{}{}", value, suffix)?;
+        }
+        self.w.write_char(']')
+    }
+
+    /// Separates entries of compounds and nested lists, which get their own lines when pretty.
+    fn separator(&mut self, index: usize) -> fmt::Result {
+        if index > 0 {
+            self.w.write_char(',')?;
+        }
+        self.newline()
+    }
+
+    /// Separates entries of scalar lists, which always stay on one line.
+    fn inline_separator(&mut self, index: usize) -> fmt::Result {
+        if index > 0 {
+            self.w.write_str(if self.pretty { ", " } else { "," })?;
+        }
+        Ok(())
+    }
+
+    fn newline(&mut self) -> fmt::Result {
+        if self.pretty {
+            self.w.write_char('\n')?;
+            for _ in 0..self.depth {
+                self.w.write_str(INDENT)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn to_snbt(value: &NbtValue, pretty: bool) -> String {
+    let mut s = String::new();
+    SnbtWriter::new(&mut s, pretty)
+        .write_value(value)
+        .expect("writing to a String cannot fail");
+    s
+}
+
+fn write_key<W: Write>(w: &mut W, key: &str) -> fmt::Result {
+    if !key.is_empty() && key.chars().all(is_unquoted_char) {
+        w.write_str(key)
+    } else {
+        write_quoted(w, key)
+    }
+}
+
+/// Quotes with `"` unless the string contains `"` but no `'`, escaping only what is necessary.
+pub(crate) fn write_quoted<W: Write>(w: &mut W, s: &str) -> fmt::Result {
+    let quote = if s.contains('"') && !s.contains('\'') {
+        '\''
+    } else {
+        '"'
+    };
+    w.write_char(quote)?;
+    for c in s.chars() {
+        if c == quote || c == '\\' {
+            w.write_char('\\')?;
+        }
+        w.write_char(c)?;
+    }
+    w.write_char(quote)
+}
+
+// ---- Helper functions ---------------------------------------------------------------------------
+fn is_unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+/// Interprets an unquoted token as number or boolean, falling back to a string like Minecraft
+/// does. Returns `None` only for tokens that cannot be a string either.
+fn parse_token(token: &str) -> Option<NbtValue> {
+    if token.is_empty() {
+        return None;
+    }
+    if token.eq_ignore_ascii_case("true") {
+        return Some(NbtValue::Boolean(true));
+    }
+    if token.eq_ignore_ascii_case("false") {
+        return Some(NbtValue::Boolean(false));
+    }
+    parse_number(token).or_else(|| Some(NbtValue::String(token.to_string())))
+}
+
+fn parse_number(token: &str) -> Option<NbtValue> {
+    let (body, suffix) = match token.char_indices().last()? {
+        (i, c) if c.is_ascii_alphabetic() => (&token[..i], Some(c.to_ascii_lowercase())),
+        _ => (token, None),
+    };
+    match suffix {
+        Some('b') if is_integer(body) => body.parse::<i8>().ok().map(|b| NbtValue::Byte(b as u8)),
+        Some('s') if is_integer(body) => body.parse().ok().map(NbtValue::Short),
+        Some('l') if is_integer(body) => body.parse().ok().map(NbtValue::Long),
+        Some('f') if is_float(body, false) => body.parse().ok().map(NbtValue::Float),
+        Some('d') if is_float(body, false) => body.parse().ok().map(NbtValue::Double),
+        None if is_integer(body) => body.parse().ok().map(NbtValue::Int),
+        None if is_float(body, true) => body.parse().ok().map(NbtValue::Double),
+        _ => None,
+    }
+}
+
+fn is_integer(s: &str) -> bool {
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Matches `[-+]?(\d+\.?|\d*\.\d+)(e[-+]?\d+)?`, requiring a decimal point if `need_point` is set.
+fn is_float(s: &str, need_point: bool) -> bool {
+    let s = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+    let (int, frac) = match mantissa.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (mantissa, None),
+    };
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    let mantissa_ok = digits(int)
+        && frac.is_none_or(digits)
+        && !(int.is_empty() && frac.is_none_or(str::is_empty));
+    let exponent_ok = exponent.is_none_or(is_integer);
+    mantissa_ok && exponent_ok && (frac.is_some() || !need_point)
+}
@@ -1,185 +1,49 @@
-use std::io::{Read, Write};
+use std::io::Write;
 
-use crate::error::NbtError;
-use crate::field::NbtField;
+use crate::encode::Encoder;
 use crate::list::NbtList;
 use crate::value::*;
-use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 
 // ---- Write Trait --------------------------------------------------------------------------------
 pub trait TagWrite {
-    fn write<W: Write>(w: &mut W, tag: u8, name: &str) -> std::io::Result<()>;
+    fn write<W: Write>(w: &mut Encoder<W>, tag: u8, name: &str) -> std::io::Result<()>;
 }
 
 pub(crate) struct TagWriteFull;
 pub(crate) struct TagWriteNone;
+pub(crate) struct TagWriteUnnamed;
 
 impl TagWrite for TagWriteFull {
-    fn write<W: Write>(w: &mut W, tag: u8, name: &str) -> std::io::Result<()> {
+    fn write<W: Write>(w: &mut Encoder<W>, tag: u8, name: &str) -> std::io::Result<()> {
         w.write_u8(tag)?;
-        w.write_u16::<BigEndian>(name.len() as u16)?;
-        w.write_all(name.as_bytes())
+        w.write_string(name)
     }
 }
 
-impl TagWrite for TagWriteNone {
-    fn write<W: Write>(_w: &mut W, _tag: u8, _name: &str) -> std::io::Result<()> {
-        Ok(())
+impl TagWrite for TagWriteUnnamed {
+    fn write<W: Write>(w: &mut Encoder<W>, tag: u8, _name: &str) -> std::io::Result<()> {
+        w.write_u8(tag)
     }
 }
 
-// ---- Helper functions ---------------------------------------------------------------------------
-pub(crate) fn write_string<W: Write>(string: &str, writer: &mut W) -> std::io::Result<()> {
-    writer.write_u16::<BigEndian>(string.len() as u16)?;
-    writer.write_all(string.as_bytes())?;
-    Ok(())
-}
-
-pub(crate) fn read_name<R: Read>(r: &mut R) -> Result<String, NbtError> {
-    let len = r.read_u16::<BigEndian>()?;
-    let mut buf = vec![0; len as usize];
-    r.read_exact(&mut buf)?;
-    Ok(String::from_utf8(buf)?)
-}
-
-pub(crate) fn read_string<R: Read>(reader: &mut R) -> Result<String, NbtError> {
-    let len = reader.read_u16::<BigEndian>()?;
-    let mut buf = vec![0; len as usize];
-    reader.read_exact(&mut buf)?;
-    Ok(String::from_utf8(buf)?)
-}
-
-pub(crate) fn read_compound<R: Read>(r: &mut R) -> Result<NbtValue, NbtError> {
-    let mut fields = Vec::new();
-    loop {
-        let field = NbtField::read(r)?;
-        if field.value == NbtValue::End {
-            break;
-        }
-        fields.push(field);
+impl TagWrite for TagWriteNone {
+    fn write<W: Write>(_w: &mut Encoder<W>, _tag: u8, _name: &str) -> std::io::Result<()> {
+        Ok(())
     }
-
-    Ok(NbtValue::Compound(fields))
 }
 
-pub(crate) fn read_list<R: Read>(r: &mut R) -> Result<NbtValue, NbtError> {
-    let tag = r.read_u8()?;
-    let len = r.read_i32::<BigEndian>()?;
-    Ok(NbtValue::List(match tag {
-        TAG_BYTE => {
-            let mut list = Vec::with_capacity(len as usize);
-            for _ in 0..len {
-                list.push(r.read_u8()?);
-            }
-            NbtList::Byte(list)
-        }
-        TAG_SHORT => {
-            let mut list = Vec::with_capacity(len as usize);
-            for _ in 0..len {
-                list.push(r.read_i16::<BigEndian>()?);
-            }
-            NbtList::Short(list)
-        }
-        TAG_INT => {
-            let mut list = Vec::with_capacity(len as usize);
-            for _ in 0..len {
-                list.push(r.read_i32::<BigEndian>()?);
-            }
-
-            NbtList::Int(list)
-        }
-        TAG_LONG => {
-            let mut list = Vec::with_capacity(len as usize);
-            for _ in 0..len {
-                list.push(r.read_i64::<BigEndian>()?);
-            }
-
-            NbtList::Long(list)
-        }
-        TAG_FLOAT => {
-            let mut list = Vec::with_capacity(len as usize);
-            for _ in 0..len {
-                list.push(r.read_f32::<BigEndian>()?);
-            }
-
-            NbtList::Float(list)
-        }
-        TAG_DOUBLE => {
-            let mut list = Vec::with_capacity(len as usize);
-            for _ in 0..len {
-                list.push(r.read_f64::<BigEndian>()?);
-            }
-
-            NbtList::Double(list)
-        }
-        TAG_STRING => {
-            let mut list = Vec::with_capacity(len as usize);
-            for _ in 0..len {
-                list.push(read_string(r)?)
-            }
-
-            NbtList::String(list)
-        }
-        TAG_LIST => {
-            let mut list = Vec::with_capacity(len as usize);
-            for _ in 0..len {
-                list.push(read_list(r)?);
-            }
-
-            NbtList::List(list)
-        }
-        TAG_COMPOUND => {
-            let mut list = Vec::with_capacity(len as usize);
-            for _ in 0..len {
-                list.push(NbtField {
-                    name: String::new(),
-                    value: read_compound(r)?,
-                });
-            }
-
-            NbtList::Compound(list)
-        }
-        TAG_LONG_ARRAY => {
-            let mut list = Vec::with_capacity(len as usize);
-            for _ in 0..len {
-                let nlongs = r.read_i32::<BigEndian>()?;
-                let mut buf = vec![0; nlongs as usize];
-                for _ in 0..nlongs {
-                    buf.push(r.read_i64::<BigEndian>()?);
-                }
-                list.push(buf);
-            }
-
-            NbtList::LongArray(list)
-        }
-        TAG_INT_ARRAY => {
-            let mut list = Vec::with_capacity(len as usize);
-            for _ in 0..len {
-                let nints = r.read_i32::<BigEndian>()?;
-                let mut buf = vec![0; nints as usize];
-                for _ in 0..nints {
-                    buf.push(r.read_i32::<BigEndian>()?);
-                }
-                list.push(buf);
-            }
-            NbtList::IntArray(list)
-        }
-        TAG_END => NbtList::End,
-        _ => panic!("Unknown tag: {}", tag),
-    }))
-}
-
-pub(crate) fn write_list<T: TagWrite, W: Write>(w: &mut W, l: &NbtList, name: &str) -> std::io::Result<()> {
+// ---- Helper functions ---------------------------------------------------------------------------
+pub(crate) fn write_list<T: TagWrite, W: Write>(w: &mut Encoder<W>, l: &NbtList, name: &str) -> std::io::Result<()> {
     T::write(w, TAG_LIST, name)?;
     match l {
         NbtList::Byte(v) => {
             w.write_u8(TAG_BYTE)?;
-            w.write_i32::<BigEndian>(v.len() as i32)?;
-            w.write_all(v)
+            w.write_len(v.len())?;
+            w.write_bytes(v)
         }
         NbtList::Boolean(v) => {
             w.write_u8(TAG_BYTE)?;
-            w.write_i32::<BigEndian>(v.len() as i32)?;
+            w.write_len(v.len())?;
             for b in v {
                 w.write_u8(if *b { 1 } else { 0 })?;
             }
@@ -187,60 +51,110 @@ pub(crate) fn write_list<T: TagWrite, W: Write>(w: &mut W, l: &NbtList, name: &s
         }
         NbtList::Short(v) => {
             w.write_u8(TAG_SHORT)?;
-            w.write_i32::<BigEndian>(v.len() as i32)?;
+            w.write_len(v.len())?;
             for s in v {
-                w.write_i16::<BigEndian>(*s)?;
+                w.write_i16(*s)?;
             }
             Ok(())
         }
         NbtList::Int(v) => {
             w.write_u8(TAG_INT)?;
-            w.write_i32::<BigEndian>(v.len() as i32)?;
+            w.write_len(v.len())?;
             for i in v {
-                w.write_i32::<BigEndian>(*i)?;
+                w.write_i32(*i)?;
             }
             Ok(())
         }
         NbtList::Long(v) => {
             w.write_u8(TAG_LONG)?;
-            w.write_i32::<BigEndian>(v.len() as i32)?;
+            w.write_len(v.len())?;
             for l in v {
-                w.write_i64::<BigEndian>(*l)?;
+                w.write_i64(*l)?;
             }
             Ok(())
         }
         NbtList::Float(v) => {
             w.write_u8(TAG_FLOAT)?;
-            w.write_i32::<BigEndian>(v.len() as i32)?;
+            w.write_len(v.len())?;
             for f in v {
-                w.write_f32::<BigEndian>(*f)?;
+                w.write_f32(*f)?;
             }
             Ok(())
         }
         NbtList::Double(v) => {
             w.write_u8(TAG_DOUBLE)?;
-            w.write_i32::<BigEndian>(v.len() as i32)?;
+            w.write_len(v.len())?;
             for d in v {
-                w.write_f64::<BigEndian>(*d)?;
+                w.write_f64(*d)?;
             }
             Ok(())
         }
         NbtList::String(v) => {
             w.write_u8(TAG_STRING)?;
-            w.write_i32::<BigEndian>(v.len() as i32)?;
+            w.write_len(v.len())?;
             for s in v {
-                write_string(s, w)?;
+                w.write_string(s)?;
             }
             Ok(())
         }
         NbtList::Compound(c) => {
             w.write_u8(TAG_COMPOUND)?;
-            w.write_i32::<BigEndian>(c.len() as i32)?;
+            w.write_len(c.len())?;
             for value in c {
-                value.write::<TagWriteNone, W>(w)?;
+                value.write_unchecked::<TagWriteNone, W>(w)?;
+            }
+            Ok(())
+        }
+        NbtList::List(l) => {
+            w.write_u8(TAG_LIST)?;
+            w.write_len(l.len())?;
+            for value in l {
+                match value {
+                    NbtValue::List(inner) => write_list::<TagWriteNone, W>(w, inner, "")?,
+                    _ => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "elements of a list of lists must be lists",
+                        ))
+                    }
+                }
+            }
+            Ok(())
+        }
+        NbtList::ByteArray(v) => {
+            w.write_u8(TAG_BYTE_ARRAY)?;
+            w.write_len(v.len())?;
+            for arr in v {
+                w.write_len(arr.len())?;
+                w.write_bytes(arr)?;
+            }
+            Ok(())
+        }
+        NbtList::IntArray(v) => {
+            w.write_u8(TAG_INT_ARRAY)?;
+            w.write_len(v.len())?;
+            for arr in v {
+                w.write_len(arr.len())?;
+                for i in arr {
+                    w.write_i32(*i)?;
+                }
+            }
+            Ok(())
+        }
+        NbtList::LongArray(v) => {
+            w.write_u8(TAG_LONG_ARRAY)?;
+            w.write_len(v.len())?;
+            for arr in v {
+                w.write_len(arr.len())?;
+                for l in arr {
+                    w.write_i64(*l)?;
+                }
             }
             Ok(())
         }
-        _ => Ok(()),
+        NbtList::End => {
+            w.write_u8(TAG_END)?;
+            w.write_len(0)
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,141 @@
+use crate::encode::Encoding;
+use crate::error::{push_field, push_index, NbtError};
+use crate::field::NbtField;
+use crate::list::NbtList;
+use crate::value::*;
+
+const MAX_STRING_LEN: usize = u16::MAX as usize;
+const MAX_ARRAY_LEN: usize = i32::MAX as usize;
+
+/// Collects everything that cannot be written as NBT, with the path of each problem.
+pub(crate) struct Validator {
+    encoding: Encoding,
+    path: String,
+    problems: Vec<NbtError>,
+    first_only: bool,
+}
+
+impl Validator {
+    pub(crate) fn problems(root: &NbtField, encoding: Encoding) -> Vec<NbtError> {
+        Validator::run(root, encoding, false)
+    }
+
+    /// Stops at the first problem instead of walking the whole tree.
+    pub(crate) fn first_problem(root: &NbtField, encoding: Encoding) -> Option<NbtError> {
+        Validator::run(root, encoding, true).pop()
+    }
+
+    fn run(root: &NbtField, encoding: Encoding, first_only: bool) -> Vec<NbtError> {
+        let mut validator = Validator {
+            encoding,
+            path: String::new(),
+            problems: Vec::new(),
+            first_only,
+        };
+        validator.string(&root.name);
+        validator.value(&root.value);
+        validator.problems
+    }
+
+    fn done(&self) -> bool {
+        self.first_only && !self.problems.is_empty()
+    }
+
+    fn value(&mut self, value: &NbtValue) {
+        match value {
+            NbtValue::String(s) => self.string(s),
+            NbtValue::ByteArray(arr) => self.len(arr.len()),
+            NbtValue::IntArray(arr) => self.len(arr.len()),
+            NbtValue::LongArray(arr) => self.len(arr.len()),
+            NbtValue::List(list) => self.list(list),
+            NbtValue::Compound(fields) => {
+                for field in fields {
+                    if self.done() {
+                        return;
+                    }
+                    self.at_field(&field.name, |v| {
+                        v.string(&field.name);
+                        v.value(&field.value);
+                    });
+                }
+            }
+            NbtValue::End if !self.path.is_empty() => self.problems.push(NbtError::MisplacedEnd {
+                path: self.path.clone(),
+            }),
+            _ => {}
+        }
+    }
+
+    fn list(&mut self, list: &NbtList) {
+        match list {
+            NbtList::Byte(v) => self.len(v.len()),
+            NbtList::Boolean(v) => self.len(v.len()),
+            NbtList::Short(v) => self.len(v.len()),
+            NbtList::Int(v) => self.len(v.len()),
+            NbtList::Long(v) => self.len(v.len()),
+            NbtList::Float(v) => self.len(v.len()),
+            NbtList::Double(v) => self.len(v.len()),
+            NbtList::String(v) => self.elements(v, |s, v| v.string(s)),
+            NbtList::List(v) => self.elements(v, |value, v| v.element(TAG_LIST, value)),
+            NbtList::Compound(v) => {
+                self.elements(v, |field, v| v.element(TAG_COMPOUND, &field.value))
+            }
+            NbtList::ByteArray(v) => self.elements(v, |arr, v| v.len(arr.len())),
+            NbtList::IntArray(v) => self.elements(v, |arr, v| v.len(arr.len())),
+            NbtList::LongArray(v) => self.elements(v, |arr, v| v.len(arr.len())),
+            NbtList::End => {}
+        }
+    }
+
+    fn elements<T>(&mut self, elements: &[T], mut check: impl FnMut(&T, &mut Self)) {
+        self.len(elements.len());
+        for (i, element) in elements.iter().enumerate() {
+            if self.done() {
+                return;
+            }
+            let len = self.path.len();
+            push_index(&mut self.path, i);
+            check(element, self);
+            self.path.truncate(len);
+        }
+    }
+
+    /// Checks an element of a list of lists or compounds, whose type is not enforced by `NbtList`.
+    fn element(&mut self, expected: u8, value: &NbtValue) {
+        if value.tag() != expected {
+            self.problems.push(NbtError::MixedList {
+                path: self.path.clone(),
+                expected,
+                found: value.tag(),
+            });
+            return;
+        }
+        self.value(value);
+    }
+
+    fn at_field(&mut self, name: &str, check: impl FnOnce(&mut Self)) {
+        let len = self.path.len();
+        push_field(&mut self.path, name);
+        check(self);
+        self.path.truncate(len);
+    }
+
+    fn string(&mut self, s: &str) {
+        let len = self.encoding.string_len(s);
+        if len > MAX_STRING_LEN {
+            self.problems.push(NbtError::StringTooLong {
+                path: self.path.clone(),
+                len,
+            });
+        }
+    }
+
+    fn len(&mut self, len: usize) {
+        if len > MAX_ARRAY_LEN {
+            self.problems.push(NbtError::ArrayTooLong {
+                path: self.path.clone(),
+                len,
+            });
+        }
+    }
+}
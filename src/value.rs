@@ -1,4 +1,5 @@
-use crate::{field::NbtField, list::NbtList};
+use crate::snbt::{parse_snbt, to_snbt, SnbtWriter};
+use crate::{compound::NbtCompound, entry::Entry, field::NbtField, list::NbtList, NbtError};
 
 pub const TAG_END: u8 = 0;
 pub const TAG_BYTE: u8 = 1;
@@ -14,7 +15,11 @@ pub const TAG_COMPOUND: u8 = 10;
 pub const TAG_INT_ARRAY: u8 = 11;
 pub const TAG_LONG_ARRAY: u8 = 12;
 
-#[derive(Debug, Clone, PartialEq)]
+/// A NBT value.
+///
+/// `Boolean` only exists on the Rust side: it is written as a `Byte` of 0 or 1, and compares
+/// equal to that `Byte`, so trees survive a write/read round trip unchanged.
+#[derive(Debug, Clone)]
 pub enum NbtValue {
     Byte(u8),
     Boolean(bool),
@@ -25,7 +30,7 @@ pub enum NbtValue {
     Double(f64),
     String(String),
     List(NbtList),
-    Compound(Vec<NbtField>),
+    Compound(NbtCompound),
     ByteArray(Vec<u8>),
     IntArray(Vec<i32>),
     LongArray(Vec<i64>),
@@ -35,45 +40,135 @@ pub enum NbtValue {
 impl NbtValue {
     pub fn get(&self, name: &str) -> Option<&NbtField> {
         match self {
-            NbtValue::Compound(fields) => fields.iter().find(|f| f.name == name),
+            NbtValue::Compound(fields) => fields.get(name),
             _ => None,
         }
     }
 
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut NbtValue> {
+        match self {
+            NbtValue::Compound(fields) => fields.get_mut(name),
+            _ => None,
+        }
+    }
+
+    /// The tag id this value is written with.
+    pub fn tag(&self) -> u8 {
+        match self {
+            NbtValue::Byte(_) | NbtValue::Boolean(_) => TAG_BYTE,
+            NbtValue::Short(_) => TAG_SHORT,
+            NbtValue::Int(_) => TAG_INT,
+            NbtValue::Long(_) => TAG_LONG,
+            NbtValue::Float(_) => TAG_FLOAT,
+            NbtValue::Double(_) => TAG_DOUBLE,
+            NbtValue::String(_) => TAG_STRING,
+            NbtValue::List(_) => TAG_LIST,
+            NbtValue::Compound(_) => TAG_COMPOUND,
+            NbtValue::ByteArray(_) => TAG_BYTE_ARRAY,
+            NbtValue::IntArray(_) => TAG_INT_ARRAY,
+            NbtValue::LongArray(_) => TAG_LONG_ARRAY,
+            NbtValue::End => TAG_END,
+        }
+    }
+
+    /// Accepts `Boolean` values as well as `Byte` values of 0 or 1.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            NbtValue::Boolean(b) => Some(*b),
+            NbtValue::Byte(0) => Some(false),
+            NbtValue::Byte(1) => Some(true),
+            _ => None,
+        }
+    }
+
+    pub fn from_snbt(snbt: &str) -> Result<NbtValue, NbtError> {
+        parse_snbt(snbt)
+    }
+
+    /// The value as SNBT that parses back to the same value, with two exceptions that SNBT cannot
+    /// express: NaN and infinite floats are written as e.g. `NaNf` and `infd`, which parse back
+    /// as strings, and `End` is written as nothing.
+    pub fn to_snbt(&self) -> String {
+        to_snbt(self, false)
+    }
+
+    pub fn to_snbt_pretty(&self) -> String {
+        to_snbt(self, true)
+    }
+
+    /// Sets a field of a compound, see [`NbtCompound::insert`]. Fails with
+    /// `NbtError::InvalidWrite` if this is not a compound.
+    pub fn insert(&mut self, field: NbtField) -> Result<Option<NbtField>, NbtError> {
+        match self {
+            NbtValue::Compound(fields) => Ok(fields.insert(field)),
+            value => Err(NbtError::InvalidWrite(format!(
+                "cannot insert a field into a value of tag {}",
+                value.tag()
+            ))),
+        }
+    }
+
+    /// Removes a field of a compound, keeping the order of the remaining fields.
+    pub fn remove(&mut self, name: &str) -> Option<NbtField> {
+        match self {
+            NbtValue::Compound(fields) => fields.remove(name),
+            _ => None,
+        }
+    }
+
+    /// Removes a field of a compound in O(1), moving the last field into its place.
     pub fn swap_remove(&mut self, name: &str) -> Option<NbtField> {
         match self {
-            NbtValue::Compound(fields) => {
-                let idx = fields.iter().position(|f| f.name == name)?;
-                Some(fields.swap_remove(idx))
-            }
+            NbtValue::Compound(fields) => fields.swap_remove(name),
+            _ => None,
+        }
+    }
+
+    /// The field `name` of a compound, for in-place updates and inserts. `None` if this is not a
+    /// compound.
+    pub fn entry(&mut self, name: &str) -> Option<Entry<'_>> {
+        match self {
+            NbtValue::Compound(fields) => Some(fields.entry(name)),
             _ => None,
         }
     }
 }
 
+impl PartialEq for NbtValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (NbtValue::Byte(a), NbtValue::Byte(b)) => a == b,
+            (NbtValue::Boolean(a), NbtValue::Boolean(b)) => a == b,
+            (NbtValue::Byte(a), NbtValue::Boolean(b))
+            | (NbtValue::Boolean(b), NbtValue::Byte(a)) => *a == *b as u8,
+            (NbtValue::Short(a), NbtValue::Short(b)) => a == b,
+            (NbtValue::Int(a), NbtValue::Int(b)) => a == b,
+            (NbtValue::Long(a), NbtValue::Long(b)) => a == b,
+            (NbtValue::Float(a), NbtValue::Float(b)) => a == b,
+            (NbtValue::Double(a), NbtValue::Double(b)) => a == b,
+            (NbtValue::String(a), NbtValue::String(b)) => a == b,
+            (NbtValue::List(a), NbtValue::List(b)) => a == b,
+            (NbtValue::Compound(a), NbtValue::Compound(b)) => a == b,
+            (NbtValue::ByteArray(a), NbtValue::ByteArray(b)) => a == b,
+            (NbtValue::IntArray(a), NbtValue::IntArray(b)) => a == b,
+            (NbtValue::LongArray(a), NbtValue::LongArray(b)) => a == b,
+            (NbtValue::End, NbtValue::End) => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::str::FromStr for NbtValue {
+    type Err = NbtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_snbt(s)
+    }
+}
+
 impl std::fmt::Display for NbtValue {
+    /// Formats the value as SNBT, `{:#}` pretty-prints it over multiple lines.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            NbtValue::Byte(b) => write!(f, "{}", b),
-            NbtValue::Boolean(b) => write!(f, "{}", b),
-            NbtValue::Short(s) => write!(f, "{}i16", s),
-            NbtValue::Int(i) => write!(f, "{}i32", i),
-            NbtValue::Long(l) => write!(f, "{}i64", l),
-            NbtValue::Float(value) => write!(f, "{}f32", value),
-            NbtValue::Double(d) => write!(f, "{}f64", d),
-            NbtValue::String(s) => write!(f, "\"{}\"", s),
-            NbtValue::List(l) => write!(f, "{}", l),
-            NbtValue::Compound(c) => {
-                write!(f, "{{")?;
-                for field in c {
-                    write!(f, "{}, ", field)?;
-                }
-                write!(f, "}}")
-            }
-            NbtValue::ByteArray(b) => write!(f, "byte[...]"),
-            NbtValue::IntArray(i) => write!(f, "int[...]"),
-            NbtValue::LongArray(l) => write!(f, "long[...]"),
-            NbtValue::End => write!(f, "End"),
-        }
+        SnbtWriter::new(f, f.alternate()).write_value(self)
     }
 }
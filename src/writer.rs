@@ -0,0 +1,204 @@
+use std::io::Write;
+
+use crate::encode::{Encoder, Encoding, WriteOptions};
+use crate::error::NbtError;
+use crate::value::*;
+
+enum Frame {
+    Compound,
+    List { tag: u8, remaining: usize },
+}
+
+/// Writes uncompressed NBT directly to a stream, without building an `NbtField` tree first.
+///
+/// Every call is checked against the open compounds and lists: list elements must match the
+/// declared element type and count, and exactly one root field may be written. Names are
+/// ignored for list elements.
+pub struct NbtWriter<W> {
+    writer: Encoder<W>,
+    encoding: Encoding,
+    stack: Vec<Frame>,
+    root_written: bool,
+}
+
+// ---- Constructors -------------------------------------------------------------------------------
+impl<W: Write> NbtWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_options(writer, &WriteOptions::default())
+    }
+
+    pub fn with_options(writer: W, options: &WriteOptions) -> Self {
+        NbtWriter {
+            writer: Encoder::new(writer, options.encoding),
+            encoding: options.encoding,
+            stack: Vec::new(),
+            root_written: false,
+        }
+    }
+
+    /// The number of compounds and lists that are currently open.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Checks that the root field is complete and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, NbtError> {
+        if !self.stack.is_empty() {
+            return Err(invalid(format!(
+                "{} unclosed compounds or lists",
+                self.stack.len()
+            )));
+        }
+        if !self.root_written {
+            return Err(invalid("no root field was written"));
+        }
+        self.writer.get_mut().flush()?;
+        Ok(self.writer.into_inner())
+    }
+}
+
+// ---- Containers ---------------------------------------------------------------------------------
+impl<W: Write> NbtWriter<W> {
+    pub fn begin_compound(&mut self, name: &str) -> Result<(), NbtError> {
+        self.header(TAG_COMPOUND, name)?;
+        self.stack.push(Frame::Compound);
+        Ok(())
+    }
+
+    /// Opens a list of `len` elements of type `tag`, e.g. `TAG_COMPOUND`.
+    pub fn begin_list(&mut self, name: &str, tag: u8, len: usize) -> Result<(), NbtError> {
+        if tag > TAG_LONG_ARRAY {
+            return Err(NbtError::UnknownTag(tag));
+        }
+        if tag == TAG_END && len > 0 {
+            return Err(invalid("only empty lists may have element type TAG_End"));
+        }
+        if i32::try_from(len).is_err() {
+            return Err(invalid("list is too long"));
+        }
+        self.header(TAG_LIST, name)?;
+        self.writer.write_u8(tag)?;
+        self.writer.write_len(len)?;
+        self.stack.push(Frame::List {
+            tag,
+            remaining: len,
+        });
+        Ok(())
+    }
+
+    /// Closes the innermost compound or list.
+    pub fn end(&mut self) -> Result<(), NbtError> {
+        match self.stack.pop() {
+            Some(Frame::Compound) => Ok(self.writer.write_u8(TAG_END)?),
+            Some(Frame::List { remaining: 0, .. }) => Ok(()),
+            Some(Frame::List { remaining, tag }) => {
+                self.stack.push(Frame::List { tag, remaining });
+                Err(invalid(format!("list is missing {} elements", remaining)))
+            }
+            None => Err(invalid("end called without an open compound or list")),
+        }
+    }
+}
+
+// ---- Fields -------------------------------------------------------------------------------------
+impl<W: Write> NbtWriter<W> {
+    pub fn field_byte(&mut self, name: &str, value: u8) -> Result<(), NbtError> {
+        self.header(TAG_BYTE, name)?;
+        Ok(self.writer.write_u8(value)?)
+    }
+
+    pub fn field_short(&mut self, name: &str, value: i16) -> Result<(), NbtError> {
+        self.header(TAG_SHORT, name)?;
+        Ok(self.writer.write_i16(value)?)
+    }
+
+    pub fn field_int(&mut self, name: &str, value: i32) -> Result<(), NbtError> {
+        self.header(TAG_INT, name)?;
+        Ok(self.writer.write_i32(value)?)
+    }
+
+    pub fn field_long(&mut self, name: &str, value: i64) -> Result<(), NbtError> {
+        self.header(TAG_LONG, name)?;
+        Ok(self.writer.write_i64(value)?)
+    }
+
+    pub fn field_float(&mut self, name: &str, value: f32) -> Result<(), NbtError> {
+        self.header(TAG_FLOAT, name)?;
+        Ok(self.writer.write_f32(value)?)
+    }
+
+    pub fn field_double(&mut self, name: &str, value: f64) -> Result<(), NbtError> {
+        self.header(TAG_DOUBLE, name)?;
+        Ok(self.writer.write_f64(value)?)
+    }
+
+    pub fn field_string(&mut self, name: &str, value: &str) -> Result<(), NbtError> {
+        if self.encoding.string_len(value) > u16::MAX as usize {
+            return Err(invalid("string is too long"));
+        }
+        self.header(TAG_STRING, name)?;
+        Ok(self.writer.write_string(value)?)
+    }
+
+    pub fn field_byte_array(&mut self, name: &str, value: &[u8]) -> Result<(), NbtError> {
+        self.array_header(TAG_BYTE_ARRAY, name, value.len())?;
+        Ok(self.writer.write_bytes(value)?)
+    }
+
+    pub fn field_int_array(&mut self, name: &str, value: &[i32]) -> Result<(), NbtError> {
+        self.array_header(TAG_INT_ARRAY, name, value.len())?;
+        for i in value {
+            self.writer.write_i32(*i)?;
+        }
+        Ok(())
+    }
+
+    pub fn field_long_array(&mut self, name: &str, value: &[i64]) -> Result<(), NbtError> {
+        self.array_header(TAG_LONG_ARRAY, name, value.len())?;
+        for l in value {
+            self.writer.write_i64(*l)?;
+        }
+        Ok(())
+    }
+
+    fn array_header(&mut self, tag: u8, name: &str, len: usize) -> Result<(), NbtError> {
+        if i32::try_from(len).is_err() {
+            return Err(invalid("array is too long"));
+        }
+        self.header(tag, name)?;
+        Ok(self.writer.write_len(len)?)
+    }
+
+    /// Writes the tag and name of a new field, or checks the element type inside a list.
+    fn header(&mut self, tag: u8, name: &str) -> Result<(), NbtError> {
+        match self.stack.last_mut() {
+            None if self.root_written => Err(invalid("the root field is already complete")),
+            Some(Frame::List { tag: expected, .. }) if *expected != tag => Err(invalid(format!(
+                "tag {} does not match the list element type {}",
+                tag, expected
+            ))),
+            Some(Frame::List { remaining: 0, .. }) => {
+                Err(invalid("list already contains all declared elements"))
+            }
+            Some(Frame::List { remaining, .. }) => {
+                *remaining -= 1;
+                Ok(())
+            }
+            Some(Frame::Compound) | None => {
+                if self.encoding.string_len(name) > u16::MAX as usize {
+                    return Err(invalid("name is too long"));
+                }
+                if self.stack.is_empty() {
+                    self.root_written = true;
+                }
+                self.writer.write_u8(tag)?;
+                Ok(self.writer.write_string(name)?)
+            }
+        }
+    }
+}
+
+// ---- Helper functions ---------------------------------------------------------------------------
+fn invalid(message: impl Into<String>) -> NbtError {
+    NbtError::InvalidWrite(message.into())
+}
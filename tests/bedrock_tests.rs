@@ -0,0 +1,151 @@
+use rnbt::*;
+
+fn little_endian() -> ReadOptions {
+    ReadOptions::default().encoding(Encoding::LittleEndian)
+}
+
+fn sample() -> NbtField {
+    let mut root = from_snbt(
+        r#"{LevelName:"Bedrock 😀",RandomSeed:-42L,Difficulty:2,spawn:[I;1,64,-3],
+            flags:[1b,0b],scale:0.5f,time:1.25d,short:-300s,bytes:[B;1b,2b],longs:[L;5L],
+            abilities:{flying:0b,walkSpeed:0.1f},lists:[[1s],[]],tags:["a","b"]}"#,
+    )
+    .unwrap();
+    root.name = "root".to_string();
+    root
+}
+
+#[test]
+fn little_endian_round_trip() {
+    let root = sample();
+    let options = WriteOptions::default().encoding(Encoding::LittleEndian);
+    let mut bytes = Vec::new();
+    write_nbt_with_options(&mut bytes, &root, &options).unwrap();
+    assert_eq!(bytes[..7], [10, 4, 0, b'r', b'o', b'o', b't']);
+    assert_eq!(
+        from_bytes_with_options(&bytes, &little_endian()).unwrap(),
+        root
+    );
+
+    let mut java = Vec::new();
+    write_nbt(&mut java, &root).unwrap();
+    assert_ne!(bytes, java);
+    assert!(from_bytes_with_options(&bytes, &ReadOptions::default()).is_err());
+
+    let events = NbtReader::with_options(bytes.as_slice(), &little_endian())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let java_events = NbtReader::new(java.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(events, java_events);
+}
+
+#[test]
+fn little_endian_layout() {
+    let bytes = [
+        10, 0, 0, //
+        3, 1, 0, b'i', 0x01, 0x02, 0, 0, //
+        8, 1, 0, b's', 4, 0, 0xf0, 0x9f, 0x98, 0x80, //
+        11, 1, 0, b'a', 1, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, //
+        0,
+    ];
+    let root = from_bytes_with_options(&bytes, &little_endian()).unwrap();
+    assert_eq!(root.get_int("i"), Some(0x0201));
+    assert_eq!(root.get_string("s").unwrap(), "😀");
+    assert_eq!(root.get_int_array("a").unwrap(), &vec![-1]);
+
+    // Bedrock strings are plain UTF-8
+    let mut writer = NbtWriter::with_options(
+        Vec::new(),
+        &WriteOptions::default().encoding(Encoding::LittleEndian),
+    );
+    writer.begin_compound("").unwrap();
+    writer.field_int("i", 0x0201).unwrap();
+    writer.field_string("s", "😀").unwrap();
+    writer.field_int_array("a", &[-1]).unwrap();
+    writer.end().unwrap();
+    assert_eq!(writer.finish().unwrap(), bytes);
+}
+
+#[test]
+fn bedrock_level_dat() {
+    let root = sample();
+    let mut file = Vec::new();
+    write_bedrock_level_dat(&mut file, 10, &root).unwrap();
+    assert_eq!(file[..4], [10, 0, 0, 0]);
+    let len = u32::from_le_bytes(file[4..8].try_into().unwrap()) as usize;
+    assert_eq!(len, file.len() - 8);
+
+    let (version, read) = read_bedrock_level_dat(&mut file.as_slice()).unwrap();
+    assert_eq!(version, 10);
+    assert_eq!(read, root);
+
+    // the header length bounds the payload
+    file[4] -= 1;
+    assert!(read_bedrock_level_dat(&mut file.as_slice()).is_err());
+    assert!(read_bedrock_level_dat(&mut &file[..6]).is_err());
+}
+
+#[test]
+fn network_round_trip() {
+    let root = sample();
+    let network = ReadOptions::default().encoding(Encoding::NetworkLittleEndian);
+    let options = WriteOptions::default().encoding(Encoding::NetworkLittleEndian);
+    let mut bytes = Vec::new();
+    write_nbt_with_options(&mut bytes, &root, &options).unwrap();
+    assert_eq!(bytes[..7], [10, 4, b'r', b'o', b'o', b't', 8]);
+    assert_eq!(from_bytes_with_options(&bytes, &network).unwrap(), root);
+
+    let mut reader = NbtReader::with_options(bytes.as_slice(), &network);
+    reader.next_event().unwrap();
+    reader.skip_container().unwrap();
+    assert_eq!(reader.next_event().unwrap(), None);
+}
+
+#[test]
+fn network_layout() {
+    let bytes = [
+        10, 0, //
+        3, 1, b'a', 0x01, //
+        4, 1, b'l', 0xd8, 0x04, //
+        9, 1, b'n', 3, 0x02, 0x03, //
+        8, 1, b's', 4, 0xf0, 0x9f, 0x98, 0x80, //
+        0,
+    ];
+    let network = ReadOptions::default().encoding(Encoding::NetworkLittleEndian);
+    let root = from_bytes_with_options(&bytes, &network).unwrap();
+    assert_eq!(root.get_int("a"), Some(-1));
+    assert_eq!(root.get_long("l"), Some(300));
+    assert_eq!(root.get_string("s").unwrap(), "😀");
+
+    let mut writer = NbtWriter::with_options(
+        Vec::new(),
+        &WriteOptions::default().encoding(Encoding::NetworkLittleEndian),
+    );
+    writer.begin_compound("").unwrap();
+    writer.field_int("a", -1).unwrap();
+    writer.field_long("l", 300).unwrap();
+    writer.begin_list("n", TAG_INT, 1).unwrap();
+    writer.field_int("", -2).unwrap();
+    writer.end().unwrap();
+    writer.field_string("s", "😀").unwrap();
+    writer.end().unwrap();
+    assert_eq!(writer.finish().unwrap(), bytes);
+
+    // a VarInt longer than five bytes
+    let overlong = [10, 0, 3, 1, b'a', 0x80, 0x80, 0x80, 0x80, 0x80, 0x01, 0];
+    assert!(matches!(
+        from_bytes_with_options(&overlong, &network)
+            .unwrap_err()
+            .kind(),
+        NbtError::InvalidVarInt
+    ));
+
+    // a string length of 4 GiB is not allocated up front
+    let huge = [10, 0xff, 0xff, 0xff, 0xff, 0x0f];
+    assert!(matches!(
+        from_bytes_with_options(&huge, &network).unwrap_err().kind(),
+        NbtError::UnexpectedEof
+    ));
+}
@@ -0,0 +1,193 @@
+use rnbt::*;
+
+fn to_bytes(snbt: &str) -> Vec<u8> {
+    let mut root = from_snbt(snbt).unwrap();
+    root.name = "root".to_string();
+    let mut buf = Vec::new();
+    write_nbt(&mut buf, &root).unwrap();
+    buf
+}
+
+const LEVEL: &str = r#"{
+    Data: {
+        LevelName: "New World",
+        RandomSeed: -4172144997891116613L,
+        Difficulty: 2b,
+        BorderSize: 59999968.0d,
+        Player: {
+            Pos: [0.5d, 64.0d, -3.25d],
+            UUID: [I; 1, -2, 3, -4],
+            Inventory: [{Slot: 0b, id: "minecraft:stone"}, {Slot: 1b, id: "minecraft:shield"}]
+        },
+        Heightmap: [L; 1L, -1L],
+        Biomes: [B; 1b, 2b, 3b],
+        Tags: ["a", "bc"]
+    }
+}"#;
+
+#[test]
+fn navigate() {
+    let bytes = to_bytes(LEVEL);
+    let root = NbtRef::new(&bytes).unwrap();
+    assert_eq!(root.name(), "root");
+
+    let data = root.get("Data").unwrap();
+    assert_eq!(
+        data.get("LevelName").unwrap().as_str().as_deref(),
+        Some("New World")
+    );
+    assert_eq!(
+        data.get("RandomSeed").unwrap().as_long(),
+        Some(-4172144997891116613)
+    );
+    assert_eq!(data.get("Difficulty").unwrap().as_byte(), Some(2));
+    assert_eq!(
+        data.get("BorderSize").unwrap().as_double(),
+        Some(59999968.0)
+    );
+    assert_eq!(data.get("Difficulty").unwrap().as_long(), None);
+    assert!(data.get("Missing").is_none());
+    assert_eq!(
+        data.iter().map(|f| f.name()).collect::<Vec<_>>(),
+        vec![
+            "LevelName",
+            "RandomSeed",
+            "Difficulty",
+            "BorderSize",
+            "Player",
+            "Heightmap",
+            "Biomes",
+            "Tags"
+        ]
+    );
+
+    let uuid = root.get_path(&["Data", "Player", "UUID"]).unwrap();
+    let uuid = uuid.as_int_array().unwrap();
+    assert_eq!(uuid.len(), 4);
+    assert_eq!(uuid.get(1), Some(-2));
+    assert_eq!(uuid.get(4), None);
+    assert_eq!(uuid.to_vec(), vec![1, -2, 3, -4]);
+
+    let heightmap = data.get("Heightmap").unwrap().as_long_array().unwrap();
+    assert_eq!(heightmap.as_bytes().len(), 16);
+    assert_eq!(heightmap.iter().collect::<Vec<_>>(), vec![1, -1]);
+    assert_eq!(
+        data.get("Biomes").unwrap().as_byte_array(),
+        Some(&[1, 2, 3][..])
+    );
+}
+
+#[test]
+fn lists() {
+    let bytes = to_bytes(LEVEL);
+    let root = NbtRef::new(&bytes).unwrap();
+    let player = root.get_path(&["Data", "Player"]).unwrap();
+
+    let pos = player.get("Pos").unwrap().as_list().unwrap();
+    assert_eq!(pos.element_tag(), 6);
+    assert_eq!(pos.len(), 3);
+    assert_eq!(pos.get(2).unwrap().as_double(), Some(-3.25));
+    assert_eq!(
+        pos.as_double_slice().unwrap().to_vec(),
+        vec![0.5, 64.0, -3.25]
+    );
+    assert!(pos.as_int_slice().is_none());
+
+    let inventory = player.get("Inventory").unwrap().as_list().unwrap();
+    let ids = inventory
+        .iter()
+        .map(|item| item.get("id").unwrap().as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["minecraft:stone", "minecraft:shield"]);
+    assert_eq!(
+        inventory.get(1).unwrap().get("Slot").unwrap().as_byte(),
+        Some(1)
+    );
+    assert!(inventory.get(2).is_none());
+
+    let tags = root.get_path(&["Data", "Tags"]).unwrap().as_list().unwrap();
+    let tags = tags
+        .into_iter()
+        .map(|t| t.as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(tags, vec!["a", "bc"]);
+}
+
+#[test]
+fn strings_borrow_from_input() {
+    let bytes = to_bytes(LEVEL);
+    let name = {
+        let root = NbtRef::new(&bytes).unwrap();
+        root.get_path(&["Data", "LevelName"])
+            .unwrap()
+            .as_str()
+            .unwrap()
+    };
+    let range = bytes.as_ptr_range();
+    assert!(range.contains(&name.as_ptr()));
+}
+
+#[test]
+fn to_owned() {
+    let bytes = to_bytes(LEVEL);
+    let root = NbtRef::new(&bytes).unwrap();
+    assert_eq!(root.to_field().unwrap(), from_bytes(&bytes).unwrap());
+    assert_eq!(
+        root.get_path(&["Data", "Player", "Pos"])
+            .unwrap()
+            .to_value()
+            .unwrap(),
+        "[0.5d, 64.0d, -3.25d]".parse::<NbtValue>().unwrap()
+    );
+}
+
+#[test]
+fn to_owned_with_options() {
+    // {a:1,b:2,a:3}
+    let bytes = [
+        10, 0, 0, 3, 0, 1, b'a', 0, 0, 0, 1, 3, 0, 1, b'b', 0, 0, 0, 2, 3, 0, 1, b'a', 0, 0, 0, 3,
+        0,
+    ];
+    let root = NbtRef::new(&bytes).unwrap();
+    assert!(matches!(
+        root.to_field().unwrap_err().kind(),
+        NbtError::DuplicateKey(name) if name == "a"
+    ));
+    let last = ReadOptions::default().duplicate_keys(DuplicateKeys::KeepLast);
+    let root = NbtRef::with_options(&bytes, &last).unwrap();
+    assert_eq!(
+        root.to_value_with_options(&last).unwrap().to_snbt(),
+        "{a:3,b:2}"
+    );
+
+    let bytes = to_bytes(LEVEL);
+    let small = ReadOptions::default().max_alloc(16);
+    let root = NbtRef::with_options(&bytes, &small).unwrap();
+    assert!(matches!(
+        root.to_field_with_options(&small).unwrap_err().kind(),
+        NbtError::LimitExceeded(ReadLimit::Alloc(16))
+    ));
+}
+
+#[test]
+fn rejects_malformed_input() {
+    let bytes = to_bytes(LEVEL);
+    for len in 0..bytes.len() {
+        assert!(NbtRef::new(&bytes[..len]).is_err(), "truncated at {}", len);
+    }
+
+    // unknown tag id inside a compound
+    let bytes = [10, 0, 0, 13, 0, 1, b'a', 0];
+    assert!(matches!(NbtRef::new(&bytes), Err(NbtError::UnknownTag(13))));
+
+    // negative array length
+    let bytes = [7, 0, 0, 0xff, 0xff, 0xff, 0xff];
+    assert!(matches!(
+        NbtRef::new(&bytes),
+        Err(NbtError::NegativeLength(-1))
+    ));
+
+    // invalid modified utf-8 in a string
+    let bytes = [8, 0, 0, 0, 2, 0xc3, 0x28];
+    assert!(matches!(NbtRef::new(&bytes), Err(NbtError::Mutf8Error(0))));
+}
@@ -0,0 +1,107 @@
+use rnbt::*;
+
+fn names(compound: &NbtCompound) -> Vec<&str> {
+    compound.iter().map(|f| f.name.as_str()).collect()
+}
+
+/// `{a:1,b:2,a:3}`, which can only be produced by hand.
+fn duplicate_bytes() -> Vec<u8> {
+    vec![
+        10, 0, 0, //
+        3, 0, 1, b'a', 0, 0, 0, 1, //
+        3, 0, 1, b'b', 0, 0, 0, 2, //
+        3, 0, 1, b'a', 0, 0, 0, 3, //
+        0,
+    ]
+}
+
+#[test]
+fn lookup_and_order() {
+    let mut compound: NbtCompound = (0..100)
+        .map(|i| NbtField::new_i32(format!("f{}", i), i))
+        .collect();
+    assert_eq!(compound.len(), 100);
+    assert_eq!(compound.get("f42"), Some(&NbtField::new_i32("f42", 42)));
+    assert!(compound.contains_key("f99"));
+    assert!(!compound.contains_key("f100"));
+
+    assert_eq!(compound.remove("f10").unwrap().value, NbtValue::Int(10));
+    assert_eq!(compound[10].name, "f11");
+    assert_eq!(compound.get("f99").unwrap().value, NbtValue::Int(99));
+    assert_eq!(compound.swap_remove("f0").unwrap().value, NbtValue::Int(0));
+    assert_eq!(compound[0].name, "f99");
+    assert_eq!(compound.get("f99").unwrap().value, NbtValue::Int(99));
+    assert_eq!(compound.get("f98").unwrap().value, NbtValue::Int(98));
+    assert_eq!(compound.len(), 98);
+
+    *compound.get_mut("f50").unwrap() = NbtValue::Int(-1);
+    for (name, value) in compound.iter_mut() {
+        if name == "f51" {
+            *value = NbtValue::Int(-2);
+        }
+    }
+    assert_eq!(compound.get("f50").unwrap().value, NbtValue::Int(-1));
+    assert_eq!(compound.get("f51").unwrap().value, NbtValue::Int(-2));
+}
+
+#[test]
+fn insertion_order_survives_round_trip() {
+    let snbt = r#"{zeta:1,alpha:2b,mid:{y:1,x:2},list:[{b:1,a:2}]}"#;
+    let root = from_snbt(snbt).unwrap();
+    assert_eq!(root.to_snbt(), snbt);
+    let mut bytes = Vec::new();
+    write_nbt(&mut bytes, &root).unwrap();
+    let read = from_bytes(&bytes).unwrap();
+    assert_eq!(
+        names(read.as_compound().unwrap()),
+        ["zeta", "alpha", "mid", "list"]
+    );
+    let mut rewritten = Vec::new();
+    write_nbt(&mut rewritten, &read).unwrap();
+    assert_eq!(rewritten, bytes);
+
+    // a vec with duplicates keeps the position of the first and the value of the last
+    let compound = NbtCompound::from(vec![
+        NbtField::new_i32("a", 1),
+        NbtField::new_i32("b", 2),
+        NbtField::new_i32("a", 3),
+    ]);
+    assert_eq!(names(&compound), ["a", "b"]);
+    assert_eq!(compound.get("a").unwrap().value, NbtValue::Int(3));
+}
+
+#[test]
+fn duplicate_keys_on_read() {
+    let bytes = duplicate_bytes();
+    let err = from_bytes(&bytes).unwrap_err();
+    assert!(matches!(err.kind(), NbtError::DuplicateKey(name) if name == "a"));
+
+    let last = ReadOptions::default().duplicate_keys(DuplicateKeys::KeepLast);
+    let root = from_bytes_with_options(&bytes, &last).unwrap();
+    assert_eq!(root.to_snbt(), "{a:3,b:2}");
+
+    let first = ReadOptions::default().duplicate_keys(DuplicateKeys::KeepFirst);
+    let root = from_bytes_with_options(&bytes, &first).unwrap();
+    assert_eq!(root.to_snbt(), "{a:1,b:2}");
+
+    let reject = ReadOptions::default().duplicate_keys(DuplicateKeys::Reject);
+    let err = from_bytes_with_options(&bytes, &reject).unwrap_err();
+    assert!(matches!(err.kind(), NbtError::DuplicateKey(name) if name == "a"));
+    assert_eq!(err.to_string(), "duplicate field \"a\" at byte 27 (tag 10)");
+
+    let mut nested = vec![10, 0, 0, 10, 0, 5, b'o', b'u', b't', b'e', b'r'];
+    nested.extend_from_slice(&duplicate_bytes()[3..]);
+    nested.push(0);
+    let err = from_bytes_with_options(&nested, &reject).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "duplicate field \"a\" at byte 35 in outer (tag 10)"
+    );
+    assert!(from_bytes_with_options(&valid(), &reject).is_ok());
+}
+
+fn valid() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_nbt(&mut bytes, &from_snbt("{a:{a:1},b:[{a:1},{a:2}]}").unwrap()).unwrap();
+    bytes
+}
@@ -0,0 +1,76 @@
+use rnbt::*;
+
+fn get_root() -> NbtField {
+    NbtField::new_compound(
+        "",
+        vec![
+            NbtField::new_string("LevelName", "New World"),
+            NbtField::new_i32("SpawnX", 16),
+            NbtField::new_long("RandomSeed", -42),
+            NbtField::new_list("Pos", NbtList::Double(vec![0.5, 64.0, 0.5])),
+        ],
+    )
+}
+
+fn compressed_read_write_test(compression: Compression) -> Vec<u8> {
+    let root = get_root();
+    let mut buf = Vec::new();
+    write_nbt_compressed(&mut buf, &root, compression).unwrap();
+    assert_eq!(from_bytes(&buf).unwrap(), root);
+    assert_eq!(read_nbt(&mut buf.as_slice()).unwrap(), root);
+    buf
+}
+
+#[test]
+fn uncompressed_read_write() {
+    let buf = compressed_read_write_test(Compression::None);
+
+    let mut plain = Vec::new();
+    write_nbt(&mut plain, &get_root()).unwrap();
+    assert_eq!(buf, plain);
+}
+
+#[test]
+fn gzip_read_write() {
+    let buf = compressed_read_write_test(Compression::gzip());
+    assert_eq!(&buf[..2], &[0x1f, 0x8b]);
+}
+
+#[test]
+fn zlib_read_write() {
+    let buf = compressed_read_write_test(Compression::Zlib(9));
+    assert_eq!(buf[0], 0x78);
+}
+
+#[test]
+fn lz4_read_write() {
+    let buf = compressed_read_write_test(Compression::Lz4);
+    assert_eq!(&buf[..8], b"LZ4Block");
+
+    // spans several 64 KiB blocks
+    let root = NbtField::new_compound(
+        "",
+        vec![NbtField::new_long_array("data", (0..50_000).collect())],
+    );
+    let mut buf = Vec::new();
+    write_nbt_compressed(&mut buf, &root, Compression::Lz4).unwrap();
+    assert_eq!(from_bytes(&buf).unwrap(), root);
+}
+
+#[test]
+fn compression_levels() {
+    for level in [0, 1, 9] {
+        compressed_read_write_test(Compression::Gzip(level));
+        compressed_read_write_test(Compression::Zlib(level));
+    }
+}
+
+#[test]
+fn detect_compression() {
+    assert_eq!(Compression::detect(0x1f), Compression::gzip());
+    assert_eq!(Compression::detect(0x78), Compression::zlib());
+    assert_eq!(Compression::detect(b'L'), Compression::Lz4);
+    for tag in 0..=12 {
+        assert_eq!(Compression::detect(tag), Compression::None);
+    }
+}
@@ -0,0 +1,131 @@
+use rnbt::*;
+
+fn snbt(s: &str) -> NbtField {
+    from_snbt(s).unwrap()
+}
+
+fn chunk() -> NbtField {
+    snbt(
+        r#"{Level:{xPos:1,zPos:-2,Status:"full",Sections:[{Y:0b,Light:[B;1b,2b]},{Y:1b,Light:[B;3b]}]}}"#,
+    )
+}
+
+#[test]
+fn identical_trees() {
+    assert!(diff(&chunk(), &chunk()).is_empty());
+
+    let mut renamed = chunk();
+    renamed.name = "other".to_string();
+    assert!(diff(&chunk(), &renamed).is_empty());
+
+    let reordered = snbt(
+        r#"{Level:{Sections:[{Light:[B;1b,2b],Y:0b},{Y:1b,Light:[B;3b]}],Status:"full",zPos:-2,xPos:1}}"#,
+    );
+    assert!(diff(&chunk(), &reordered).is_empty());
+}
+
+#[test]
+fn boolean_equals_byte() {
+    let old = NbtField::new_bool("a", true);
+    let new = NbtField {
+        name: "a".to_string(),
+        value: NbtValue::Byte(1),
+    };
+    assert!(diff(&old, &new).is_empty());
+}
+
+#[test]
+fn changes() {
+    let new = snbt(
+        r#"{Level:{xPos:2,zPos:-2L,Sections:[{Y:0b,Light:[B;1b,2b,4b]},{Y:2b}],LastUpdate:10L}}"#,
+    );
+    assert_eq!(
+        diff(&chunk(), &new),
+        vec![
+            NbtChange::ValueChanged {
+                path: "Level.xPos".to_string(),
+                old: NbtValue::Int(1),
+                new: NbtValue::Int(2),
+            },
+            NbtChange::TypeChanged {
+                path: "Level.zPos".to_string(),
+                old: NbtValue::Int(-2),
+                new: NbtValue::Long(-2),
+            },
+            NbtChange::Removed {
+                path: "Level.Status".to_string(),
+                value: NbtValue::String("full".to_string()),
+            },
+            NbtChange::LengthChanged {
+                path: "Level.Sections[0].Light".to_string(),
+                old: 2,
+                new: 3,
+            },
+            NbtChange::Added {
+                path: "Level.Sections[0].Light[2]".to_string(),
+                value: NbtValue::Byte(4),
+            },
+            NbtChange::ValueChanged {
+                path: "Level.Sections[1].Y".to_string(),
+                old: NbtValue::Byte(1),
+                new: NbtValue::Byte(2),
+            },
+            NbtChange::Removed {
+                path: "Level.Sections[1].Light".to_string(),
+                value: snbt("{a:[B;3b]}").get("a").unwrap().value.clone(),
+            },
+            NbtChange::Added {
+                path: "Level.LastUpdate".to_string(),
+                value: NbtValue::Long(10),
+            },
+        ]
+    );
+}
+
+#[test]
+fn lists() {
+    let old = snbt("{a:[1,2,3],b:[1,2],c:[]}");
+    let new = snbt(r#"{a:[1],b:["x","y"],c:[1.0f]}"#);
+    let changes = diff(&old, &new);
+    let paths: Vec<&str> = changes.iter().map(|c| c.path()).collect();
+    assert_eq!(paths, vec!["a", "a[1]", "a[2]", "b", "c", "c[0]"]);
+    assert!(matches!(
+        changes[0],
+        NbtChange::LengthChanged { old: 3, new: 1, .. }
+    ));
+    assert!(matches!(changes[1], NbtChange::Removed { .. }));
+    assert!(matches!(changes[3], NbtChange::TypeChanged { .. }));
+    assert!(matches!(
+        changes[4],
+        NbtChange::LengthChanged { old: 0, new: 1, .. }
+    ));
+    assert!(matches!(changes[5], NbtChange::Added { .. }));
+}
+
+#[test]
+fn root_change() {
+    let changes = diff(&NbtField::new_i32("", 1), &NbtField::new_string("", "1"));
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path(), "");
+    assert_eq!(render_diff(&changes), "~ (root): 1 -> \"1\" (tag 3 -> 8)\n");
+}
+
+#[test]
+fn rendering() {
+    let old = snbt(r#"{a:1,b:"x",c:[1,2],"d e":0b}"#);
+    let new = snbt(r#"{a:2,c:[1],"d e":0b,f:{g:1s}}"#);
+    assert_eq!(
+        render_diff(&diff(&old, &new)),
+        "~ a: 1 -> 2\n\
+         - b: \"x\"\n\
+         # c: 2 -> 1 elements\n\
+         - c[1]: 2\n\
+         + f: {g:1s}\n"
+    );
+
+    let long = NbtField::new_string("", "x".repeat(200));
+    let changes = diff(&NbtField::new_string("", ""), &long);
+    let line = changes[0].to_string();
+    assert!(line.ends_with("xxx..."));
+    assert!(line.len() < 200);
+}
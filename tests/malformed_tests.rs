@@ -0,0 +1,333 @@
+use rnbt::*;
+
+fn kind<T>(result: &Result<T, NbtError>) -> Option<&NbtError> {
+    result.as_ref().err().map(NbtError::kind)
+}
+
+fn valid_bytes() -> Vec<u8> {
+    let root = from_snbt(
+        r#"{name:"chunk",pos:[I;1,2],blocks:[B;1b,2b,3b],states:[L;7L],
+            sections:[{Y:0b,palette:["air","stone"]},{Y:1b,palette:["air"]}],
+            heights:[1s,2s],scale:0.5f}"#,
+    )
+    .unwrap();
+    let mut buf = Vec::new();
+    write_nbt(&mut buf, &root).unwrap();
+    buf
+}
+
+#[test]
+fn unknown_tag() {
+    assert!(matches!(
+        kind(&from_bytes(&[42])),
+        Some(NbtError::UnknownTag(42))
+    ));
+    let bytes = [10, 0, 0, 1, 0, 1, b'a', 5, 99, 0, 0];
+    assert!(matches!(
+        kind(&from_bytes(&bytes)),
+        Some(NbtError::UnknownTag(99))
+    ));
+}
+
+#[test]
+fn negative_lengths() {
+    for tag in [7, 11, 12] {
+        let bytes = [tag, 0, 0, 0x80, 0, 0, 0];
+        assert!(matches!(
+            kind(&from_bytes(&bytes)),
+            Some(NbtError::NegativeLength(i32::MIN))
+        ));
+    }
+    let bytes = [9, 0, 0, 1, 0xff, 0xff, 0xff, 0xfe];
+    assert!(matches!(
+        kind(&from_bytes(&bytes)),
+        Some(NbtError::NegativeLength(-2))
+    ));
+}
+
+#[test]
+fn huge_lengths_do_not_allocate() {
+    for tag in [7, 11, 12] {
+        let bytes = [tag, 0, 0, 0x7f, 0xff, 0xff, 0xff, 1, 2, 3];
+        assert!(matches!(
+            kind(&from_bytes(&bytes)),
+            Some(NbtError::UnexpectedEof)
+        ));
+    }
+    let bytes = [9, 0, 0, 4, 0x7f, 0xff, 0xff, 0xff, 0, 0];
+    assert!(matches!(
+        kind(&from_bytes(&bytes)),
+        Some(NbtError::UnexpectedEof)
+    ));
+}
+
+#[test]
+fn invalid_list_type() {
+    let bytes = [9, 0, 0, 0, 0, 0, 0, 1];
+    assert!(matches!(
+        kind(&from_bytes(&bytes)),
+        Some(NbtError::InvalidListType(0))
+    ));
+    let bytes = [9, 0, 0, 13, 0, 0, 0, 0];
+    assert!(matches!(
+        kind(&from_bytes(&bytes)),
+        Some(NbtError::InvalidListType(13))
+    ));
+    assert!(matches!(
+        kind(&NbtReader::new(&bytes[..]).next().unwrap()),
+        Some(NbtError::InvalidListType(13))
+    ));
+    assert!(matches!(
+        kind(&NbtRef::new(&bytes)),
+        Some(NbtError::InvalidListType(13))
+    ));
+}
+
+#[test]
+fn truncated() {
+    let bytes = valid_bytes();
+    assert!(from_bytes(&bytes).is_ok());
+    for len in 0..bytes.len() {
+        assert!(
+            matches!(
+                kind(&from_bytes(&bytes[..len])),
+                Some(NbtError::UnexpectedEof)
+            ),
+            "truncated at {}",
+            len
+        );
+    }
+}
+
+#[test]
+fn corrupted_input_never_panics() {
+    let bytes = valid_bytes();
+    let mut seed = 0x2545_f491_u32;
+    let mut next = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed as usize
+    };
+    for _ in 0..2000 {
+        let mut corrupt = bytes.clone();
+        for _ in 0..1 + next() % 4 {
+            let at = next() % corrupt.len();
+            corrupt[at] = next() as u8;
+        }
+        let _ = from_bytes(&corrupt);
+        let _ = NbtRef::new(&corrupt).map(|r| r.to_value());
+        for event in NbtReader::new(corrupt.as_slice()) {
+            if event.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+fn nested_lists(depth: usize) -> Vec<u8> {
+    let mut bytes = vec![9, 0, 0];
+    for _ in 1..depth {
+        bytes.extend_from_slice(&[9, 0, 0, 0, 1]);
+    }
+    bytes.extend_from_slice(&[0, 0, 0, 0, 0]);
+    bytes
+}
+
+fn nested_compounds(depth: usize) -> Vec<u8> {
+    let mut bytes = vec![10, 0, 0];
+    for _ in 1..depth {
+        bytes.extend_from_slice(&[10, 0, 1, b'a']);
+    }
+    bytes.resize(bytes.len() + depth, 0);
+    bytes
+}
+
+#[test]
+fn depth_limit() {
+    assert!(from_bytes(&nested_lists(512)).is_ok());
+    assert!(from_bytes(&nested_compounds(512)).is_ok());
+    assert!(matches!(
+        kind(&from_bytes(&nested_compounds(513))),
+        Some(NbtError::LimitExceeded(ReadLimit::Depth(512)))
+    ));
+    let deep = nested_lists(513);
+    assert!(matches!(
+        kind(&from_bytes(&deep)),
+        Some(NbtError::LimitExceeded(ReadLimit::Depth(512)))
+    ));
+    assert!(matches!(
+        kind(&NbtRef::new(&deep)),
+        Some(NbtError::LimitExceeded(ReadLimit::Depth(512)))
+    ));
+    assert!(NbtReader::new(deep.as_slice()).any(|e| matches!(
+        kind(&e),
+        Some(NbtError::LimitExceeded(ReadLimit::Depth(512)))
+    )));
+    let mut reader = NbtReader::new(deep.as_slice());
+    reader.next_event().unwrap();
+    assert!(reader.skip_container().is_err());
+
+    // far deeper than the stack could handle without the limit
+    let options = ReadOptions::default().max_depth(8);
+    assert!(from_bytes_with_options(&nested_lists(8), &options).is_ok());
+    assert!(matches!(
+        kind(&from_bytes_with_options(&nested_lists(100_000), &options)),
+        Some(NbtError::LimitExceeded(ReadLimit::Depth(8)))
+    ));
+    assert!(NbtRef::with_options(&nested_lists(9), &options).is_err());
+}
+
+#[test]
+fn alloc_limit() {
+    let bytes = valid_bytes();
+    let options = ReadOptions::default().max_alloc(64);
+    assert!(matches!(
+        kind(&from_bytes_with_options(&bytes, &options)),
+        Some(NbtError::LimitExceeded(ReadLimit::Alloc(64)))
+    ));
+    assert!(from_bytes_with_options(&bytes, &ReadOptions::default().max_alloc(1 << 20)).is_ok());
+
+    // the name index of a compound stores a second copy of each name
+    let mut bytes = vec![10, 0, 0, 1, 0x03, 0xe8];
+    bytes.resize(bytes.len() + 1000, b'a');
+    bytes.extend_from_slice(&[1, 0]);
+    let fields_only = 1000 + std::mem::size_of::<NbtField>();
+    assert!(matches!(
+        kind(&from_bytes_with_options(
+            &bytes,
+            &ReadOptions::default().max_alloc(fields_only)
+        )),
+        Some(NbtError::LimitExceeded(ReadLimit::Alloc(_)))
+    ));
+    let with_index = ReadOptions::default().max_alloc(2 * fields_only);
+    assert!(from_bytes_with_options(&bytes, &with_index).is_ok());
+
+    // the claimed length is rejected before anything is read
+    let bytes = [11, 0, 0, 0x7f, 0xff, 0xff, 0xff];
+    assert!(matches!(
+        kind(&from_bytes_with_options(
+            &bytes,
+            &ReadOptions::default().max_alloc(1 << 20)
+        )),
+        Some(NbtError::LimitExceeded(ReadLimit::Alloc(_)))
+    ));
+    let events = NbtReader::with_options(&bytes[..], &ReadOptions::default().max_alloc(1 << 20));
+    assert!(events
+        .last()
+        .is_some_and(|e| matches!(kind(&e), Some(NbtError::LimitExceeded(ReadLimit::Alloc(_))))));
+}
+
+#[test]
+fn size_limit() {
+    let bytes = valid_bytes();
+    let len = bytes.len() as u64;
+    let exact = ReadOptions::default().max_size(len);
+    assert!(from_bytes_with_options(&bytes, &exact).is_ok());
+    assert!(NbtRef::with_options(&bytes, &exact).is_ok());
+    assert_eq!(
+        NbtReader::with_options(bytes.as_slice(), &exact).count(),
+        NbtReader::new(bytes.as_slice()).count()
+    );
+
+    let short = ReadOptions::default().max_size(len - 1);
+    assert!(matches!(
+        kind(&from_bytes_with_options(&bytes, &short)),
+        Some(NbtError::LimitExceeded(ReadLimit::Size(_)))
+    ));
+    assert!(matches!(
+        kind(&NbtRef::with_options(&bytes, &short)),
+        Some(NbtError::LimitExceeded(ReadLimit::Size(_)))
+    ));
+    let mut reader = NbtReader::with_options(bytes.as_slice(), &short);
+    assert!(matches!(
+        kind(&reader.skip_container()),
+        Some(NbtError::LimitExceeded(ReadLimit::Size(_)))
+    ));
+
+    // the limit applies to decompressed data
+    let mut compressed = Vec::new();
+    write_nbt_compressed(
+        &mut compressed,
+        &from_bytes(&bytes).unwrap(),
+        Compression::gzip(),
+    )
+    .unwrap();
+    assert!((compressed.len() as u64) < len);
+    assert!(matches!(
+        kind(&read_nbt_with_options(&mut compressed.as_slice(), &short)),
+        Some(NbtError::LimitExceeded(ReadLimit::Size(_)))
+    ));
+}
+
+#[test]
+fn error_context() {
+    let bytes = valid_bytes();
+    let at = bytes.windows(3).rposition(|w| w == b"air").unwrap();
+    let truncated = &bytes[..at + 1];
+    let err = from_bytes(truncated).unwrap_err();
+    match &err {
+        NbtError::At {
+            offset, path, tag, ..
+        } => {
+            assert_eq!(*offset, truncated.len() as u64);
+            assert_eq!(path, "sections[1].palette[0]");
+            assert_eq!(*tag, TAG_STRING);
+        }
+        e => panic!("expected position context, got {:?}", e),
+    }
+    assert!(matches!(err.kind(), NbtError::UnexpectedEof));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "unexpected end of data at byte {} in sections[1].palette[0] (tag 8)",
+            truncated.len()
+        )
+    );
+    let events = NbtReader::new(truncated).find_map(Result::err).unwrap();
+    assert_eq!(events.to_string(), err.to_string());
+
+    let err = from_bytes(&[10, 0, 0, 11, 0, 3, b'p', b'o', b's', 0, 0, 0, 2, 0]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unexpected end of data at byte 14 in pos[0] (tag 11)"
+    );
+}
+
+#[test]
+fn root_compound_requirement() {
+    let strict = ReadOptions::default().require_compound_root(true);
+    let int_root = [3, 0, 1, b'a', 0, 0, 0, 7];
+    assert_eq!(from_bytes(&int_root).unwrap(), NbtField::new_i32("a", 7));
+    assert!(matches!(
+        kind(&from_bytes_with_options(&int_root, &strict)),
+        Some(NbtError::RootNotCompoundError)
+    ));
+    assert!(matches!(
+        kind(&from_bytes_with_options(&[0], &strict)),
+        Some(NbtError::RootNotCompoundError)
+    ));
+    assert!(matches!(
+        kind(&read_unnamed_nbt_with_options(
+            &mut [8u8, 0, 0].as_slice(),
+            &strict
+        )),
+        Some(NbtError::RootNotCompoundError)
+    ));
+    assert!(matches!(
+        NbtRef::with_options(&int_root, &strict),
+        Err(NbtError::RootNotCompoundError)
+    ));
+    let mut reader = NbtReader::with_options(int_root.as_slice(), &strict);
+    assert!(matches!(
+        kind(&reader.next_event()),
+        Some(NbtError::RootNotCompoundError)
+    ));
+
+    let bytes = valid_bytes();
+    assert!(from_bytes_with_options(&bytes, &strict).is_ok());
+    assert!(NbtRef::with_options(&bytes, &strict).is_ok());
+    assert!(NbtReader::with_options(bytes.as_slice(), &strict)
+        .collect::<Result<Vec<_>, _>>()
+        .is_ok());
+}
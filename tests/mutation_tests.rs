@@ -0,0 +1,159 @@
+use rnbt::*;
+
+fn player() -> NbtField {
+    from_snbt(
+        r#"{Health:20.0f,XpLevel:3,Inventory:[{Slot:0b,id:"minecraft:stone",Count:1b},
+            {Slot:1b,id:"minecraft:dirt",Count:64b}],abilities:{flying:0b,mayfly:0b}}"#,
+    )
+    .unwrap()
+}
+
+fn names(field: &NbtField) -> Vec<&str> {
+    field
+        .as_compound()
+        .unwrap()
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect()
+}
+
+#[test]
+fn get_mut_and_paths() {
+    let mut root = player();
+    *root.get_mut("XpLevel").unwrap() = NbtValue::Int(4);
+    assert_eq!(root.get_int("XpLevel"), Some(4));
+    assert!(root.get_mut("missing").is_none());
+
+    *root
+        .get_indexed_path_mut(&["Inventory", "1", "Count"])
+        .unwrap() = NbtValue::Byte(32);
+    assert_eq!(
+        root.get_indexed_path(&["Inventory", "1"])
+            .unwrap()
+            .get_byte("Count"),
+        Some(32)
+    );
+    assert_eq!(
+        root.get_indexed_path(&["Inventory", "0", "id"])
+            .unwrap()
+            .as_string()
+            .unwrap(),
+        "minecraft:stone"
+    );
+    assert!(root.get_indexed_path_mut(&["Inventory", "2"]).is_none());
+    assert!(root.get_indexed_path_mut(&["Inventory", "first"]).is_none());
+    assert!(root.get_indexed_path_mut(&["Health", "0"]).is_none());
+    assert!(root.get_path(&["Inventory", "0"]).is_none());
+    assert!(root.get_path_mut(&["Inventory", "0"]).is_none());
+    *root.get_path_mut(&["abilities", "flying"]).unwrap() = NbtValue::Byte(1);
+    assert_eq!(
+        root.get_path(&["abilities"]).unwrap().get_byte("flying"),
+        Some(1)
+    );
+
+    let Some(NbtValue::List(inventory)) = root.get_mut("Inventory") else {
+        panic!("no inventory");
+    };
+    inventory.as_compound_list_mut().unwrap().pop();
+    assert_eq!(
+        root.get_list("Inventory")
+            .unwrap()
+            .as_compound_list()
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn insert_and_remove_keep_order() {
+    let mut root = player();
+    assert_eq!(
+        root.insert(NbtField::new_i32("XpLevel", 10)).unwrap(),
+        Some(NbtField::new_i32("XpLevel", 3))
+    );
+    assert_eq!(root.insert(NbtField::new_float("XpP", 0.5)).unwrap(), None);
+    assert_eq!(
+        names(&root),
+        vec!["Health", "XpLevel", "Inventory", "abilities", "XpP"]
+    );
+    assert_eq!(root.get_int("XpLevel"), Some(10));
+
+    assert_eq!(
+        root.remove("Health"),
+        Some(NbtField::new_float("Health", 20.0))
+    );
+    assert_eq!(root.remove("Health"), None);
+    assert_eq!(
+        names(&root),
+        vec!["XpLevel", "Inventory", "abilities", "XpP"]
+    );
+    assert_eq!(NbtField::new_i32("a", 1).remove("a"), None);
+
+    let item = root.get_indexed_path_mut(&["Inventory", "0"]).unwrap();
+    item.remove("Slot");
+    item.insert(NbtField::new_string("id", "minecraft:granite"))
+        .unwrap();
+    assert_eq!(item.to_snbt(), r#"{id:"minecraft:granite",Count:1b}"#);
+}
+
+#[test]
+fn entry_api() {
+    let mut root = player();
+    let abilities = root
+        .entry("abilities")
+        .unwrap()
+        .or_insert_with(|| NbtValue::Compound(NbtCompound::new()));
+    *abilities
+        .entry("flying")
+        .unwrap()
+        .or_insert(NbtValue::Byte(0)) = NbtValue::Byte(1);
+    abilities
+        .entry("walkSpeed")
+        .unwrap()
+        .or_insert(NbtValue::Float(0.1));
+    assert_eq!(abilities.to_snbt(), "{flying:1b,mayfly:0b,walkSpeed:0.1f}");
+
+    let entry = root.entry("Score").unwrap();
+    assert_eq!(entry.name(), "Score");
+    assert!(!entry.is_occupied());
+    entry
+        .and_modify(|_| panic!("no field to modify"))
+        .or_insert(NbtValue::Int(0));
+    root.entry("Score")
+        .unwrap()
+        .and_modify(|v| {
+            if let NbtValue::Int(i) = v {
+                *i += 5;
+            }
+        })
+        .or_insert(NbtValue::Int(0));
+    assert_eq!(root.get_int("Score"), Some(5));
+    assert_eq!(names(&root).last(), Some(&"Score"));
+
+    let item = root.get_indexed_path_mut(&["Inventory", "1"]).unwrap();
+    item.entry("tag")
+        .unwrap()
+        .or_insert_with(|| NbtValue::Compound(NbtCompound::new()))
+        .insert(NbtField::new_i32("Damage", 2))
+        .unwrap();
+    assert_eq!(
+        root.get_indexed_path(&["Inventory", "1", "tag"])
+            .unwrap()
+            .get_int("Damage"),
+        Some(2)
+    );
+}
+
+#[test]
+fn non_compounds() {
+    let mut int = NbtField::new_i32("a", 1);
+    assert!(matches!(
+        int.insert(NbtField::new_i32("b", 2)),
+        Err(NbtError::InvalidWrite(_))
+    ));
+    assert!(int.entry("b").is_none());
+    assert!(int.get_mut("b").is_none());
+    assert_eq!(int.remove("b"), None);
+    assert_eq!(int, NbtField::new_i32("a", 1));
+}
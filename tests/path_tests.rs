@@ -0,0 +1,179 @@
+use rnbt::*;
+
+fn player() -> NbtField {
+    from_snbt(
+        r#"{Pos:[1.5d,64.0d,-3.25d],Tags:["a","b"],seeds:[I;3,1,4,1],
+            Inventory:[
+                {Slot:0b,id:"minecraft:stone",Count:1b,tag:{display:{Name:"Rock"}}},
+                {Slot:1b,id:"minecraft:dirt",Count:64b},
+                {Slot:2b,id:"minecraft:stone",Count:5b,tag:{Enchantments:[{id:"sharpness",lvl:2s}]}}
+            ],
+            Level:{Sections:[{Y:0b},{Y:1b},{Y:2b}]},
+            grid:[[1,2],[3]],
+            "odd name":{x:1}}"#,
+    )
+    .unwrap()
+}
+
+fn query(root: &NbtField, path: &str) -> Vec<NbtValue> {
+    root.query(&path.parse().unwrap())
+        .into_iter()
+        .map(|v| v.into_owned())
+        .collect()
+}
+
+fn snbt(root: &NbtField, path: &str) -> Vec<String> {
+    query(root, path).iter().map(NbtValue::to_snbt).collect()
+}
+
+#[test]
+fn selects_like_data_paths() {
+    let root = player();
+    assert_eq!(snbt(&root, "Pos[1]"), ["64d"]);
+    assert_eq!(snbt(&root, "Pos[-1]"), ["-3.25d"]);
+    assert!(query(&root, "Pos[3]").is_empty());
+    assert!(query(&root, "Pos[-4]").is_empty());
+    assert_eq!(snbt(&root, "seeds[]"), ["3", "1", "4", "1"]);
+    assert_eq!(snbt(&root, "Tags[0]"), [r#""a""#]);
+    assert_eq!(snbt(&root, "Level.Sections[-1].Y"), ["2b"]);
+    assert_eq!(snbt(&root, "Level.Sections[].Y"), ["0b", "1b", "2b"]);
+    assert_eq!(snbt(&root, "grid[0][1]"), ["2"]);
+    assert_eq!(snbt(&root, "grid[].[0]"), ["1", "3"]);
+    assert_eq!(snbt(&root, "\"odd name\".x"), ["1"]);
+    assert_eq!(
+        snbt(&root, "Inventory[{Slot:0b}].tag.display.Name"),
+        [r#""Rock""#]
+    );
+    assert_eq!(
+        snbt(&root, r#"Inventory[{id:"minecraft:stone"}].Count"#),
+        ["1b", "5b"]
+    );
+    assert_eq!(
+        snbt(
+            &root,
+            "Inventory[{tag:{Enchantments:[{id:\"sharpness\"}]}}].Slot"
+        ),
+        ["2b"]
+    );
+    assert_eq!(
+        snbt(&root, "Level{Sections:[{Y:1b}]}.Sections[0].Y"),
+        ["0b"]
+    );
+    assert!(query(&root, "Level{Sections:[{Y:3b}]}").is_empty());
+    assert!(query(&root, "Level{Sections:[]}").is_empty());
+    assert_eq!(query(&root, "{Tags:[\"b\"]}").len(), 1);
+    assert!(query(&root, "{Tags:[\"c\"]}").is_empty());
+    assert!(query(&root, "missing.path[0]").is_empty());
+    assert!(query(&root, "Pos.x").is_empty());
+
+    // values inside compounds are borrowed
+    let path = "Level.Sections".parse().unwrap();
+    assert!(matches!(
+        root.query(&path)[0],
+        std::borrow::Cow::Borrowed(_)
+    ));
+}
+
+#[test]
+fn mutable_queries() {
+    let mut root = player();
+    let path: NbtPath = r#"Inventory[{id:"minecraft:stone"}].Count"#.parse().unwrap();
+    for mut count in root.query_mut(&path) {
+        count.set(NbtValue::Byte(32)).unwrap();
+    }
+    assert_eq!(snbt(&root, "Inventory[].Count"), ["32b", "64b", "32b"]);
+
+    let mut pos = root.query_mut(&"Pos[-1]".parse().unwrap());
+    assert!(matches!(pos[0], NbtValueMut::Double(_)));
+    assert_eq!(pos[0].to_value(), NbtValue::Double(-3.25));
+    assert!(matches!(
+        pos[0].set(NbtValue::Int(1)),
+        Err(NbtError::InvalidWrite(_))
+    ));
+    pos[0].set(NbtValue::Double(0.5)).unwrap();
+    assert_eq!(snbt(&root, "Pos[2]"), ["0.5d"]);
+
+    for mut seed in root.query_mut(&"seeds[]".parse().unwrap()) {
+        if let NbtValueMut::Int(i) = &mut seed {
+            **i *= 10;
+        }
+    }
+    assert_eq!(snbt(&root, "seeds"), ["[I;30,10,40,10]"]);
+
+    for mut section in root.query_mut(&"Level.Sections[{Y:1b}]".parse().unwrap()) {
+        section
+            .set(from_snbt("{Y:1b,empty:1b}").unwrap().value)
+            .unwrap();
+    }
+    assert_eq!(snbt(&root, "Level.Sections[1].empty"), ["1b"]);
+    assert!(root.query_mut(&"Tags[5]".parse().unwrap()).is_empty());
+
+    // elements of lists of compounds and lists keep the type of their list
+    let mut item = root.query_mut(&"Inventory[0]".parse().unwrap());
+    assert_eq!(item[0].tag(), TAG_COMPOUND);
+    assert!(matches!(
+        item[0].set(NbtValue::Int(5)),
+        Err(NbtError::InvalidWrite(_))
+    ));
+    let mut row = root.query_mut(&"grid[1]".parse().unwrap());
+    assert!(matches!(
+        row[0].set(NbtValue::Int(5)),
+        Err(NbtError::InvalidWrite(_))
+    ));
+    row[0].set(NbtValue::List(NbtList::Short(vec![4]))).unwrap();
+    assert_eq!(snbt(&root, "grid"), ["[[1,2],[4s]]"]);
+    let mut buf = Vec::new();
+    write_nbt(&mut buf, &root).unwrap();
+}
+
+#[test]
+fn parse_and_display() {
+    for path in [
+        "Inventory[{Slot:0b}].tag.display.Name",
+        "Pos[1]",
+        "Items[]",
+        "Level.Sections[-1]",
+        "{Tags:[\"a\"]}.Tags[0]",
+        "\"odd name\".x",
+        "Level{Sections:[]}.Sections",
+    ] {
+        assert_eq!(NbtPath::parse(path).unwrap().to_string(), path);
+    }
+    assert_eq!(
+        NbtPath::parse("grid[0].[1]").unwrap().to_string(),
+        "grid[0][1]"
+    );
+
+    for (path, position) in [
+        ("", 0),
+        ("a.", 2),
+        ("a..b", 2),
+        ("a[x]", 2),
+        ("a[0", 3),
+        ("a b", 1),
+        ("a.{b:1}", 2),
+    ] {
+        assert!(
+            matches!(
+                NbtPath::parse(path),
+                Err(NbtError::InvalidPath { position: p, .. }) if p == position
+            ),
+            "{}",
+            path
+        );
+    }
+    for (path, position) in [("a{b:}", 4), ("\"a", 2), ("a[{b:\"c}]", 9)] {
+        assert!(
+            matches!(
+                NbtPath::parse(path),
+                Err(NbtError::InvalidPath { path: ref p, position: q, .. }) if p == path && q == position
+            ),
+            "{}",
+            path
+        );
+    }
+    assert_eq!(
+        NbtPath::parse("a[x]").unwrap_err().to_string(),
+        "invalid NBT path \"a[x]\" at position 2: expected index"
+    );
+}
@@ -0,0 +1,61 @@
+use rnbt::*;
+
+#[test]
+fn unnamed_compound_root() {
+    let mut root = from_snbt(r#"{id:"minecraft:stone",count:1b,components:{damage:3}}"#).unwrap();
+    let mut bytes = Vec::new();
+    write_unnamed_nbt(&mut bytes, &root).unwrap();
+    assert_eq!(bytes[..3], [10, 8, 0]);
+    assert_eq!(read_unnamed_nbt(&mut bytes.as_slice()).unwrap(), root);
+
+    // the name is dropped on write
+    let mut named = Vec::new();
+    write_nbt(&mut named, &root).unwrap();
+    assert_eq!(named.len(), bytes.len() + 2);
+    root.name = "item".to_string();
+    let mut renamed = Vec::new();
+    write_unnamed_nbt(&mut renamed, &root).unwrap();
+    assert_eq!(renamed, bytes);
+}
+
+#[test]
+fn non_compound_roots() {
+    // a plain text chat component
+    let bytes = [8, 0, 5, b'h', b'e', b'l', b'l', b'o'];
+    let root = read_unnamed_nbt(&mut bytes.as_slice()).unwrap();
+    assert_eq!(root, NbtField::new_string("", "hello"));
+    let mut written = Vec::new();
+    write_unnamed_nbt(&mut written, &root).unwrap();
+    assert_eq!(written, bytes);
+
+    let root = NbtField {
+        name: String::new(),
+        value: NbtValue::List(NbtList::String(vec!["a".to_string(), "b".to_string()])),
+    };
+    let mut bytes = Vec::new();
+    write_unnamed_nbt(&mut bytes, &root).unwrap();
+    assert_eq!(read_unnamed_nbt(&mut bytes.as_slice()).unwrap(), root);
+
+    // an empty optional item is a lone TAG_End
+    let root = read_unnamed_nbt(&mut [0u8].as_slice()).unwrap();
+    assert_eq!(root.value, NbtValue::End);
+}
+
+#[test]
+fn unnamed_errors() {
+    assert!(matches!(
+        read_unnamed_nbt(&mut [13u8].as_slice()).unwrap_err().kind(),
+        NbtError::UnknownTag(13)
+    ));
+    assert!(matches!(
+        read_unnamed_nbt(&mut [3u8, 0, 0].as_slice())
+            .unwrap_err()
+            .kind(),
+        NbtError::UnexpectedEof
+    ));
+    let options = ReadOptions::default().max_depth(1);
+    let nested = from_snbt("{a:{b:{}}}").unwrap();
+    let mut bytes = Vec::new();
+    write_unnamed_nbt(&mut bytes, &nested).unwrap();
+    assert!(read_unnamed_nbt_with_options(&mut bytes.as_slice(), &options).is_err());
+}
@@ -0,0 +1,186 @@
+use rnbt::*;
+
+fn kind<T>(result: &Result<T, NbtError>) -> Option<&NbtError> {
+    result.as_ref().err().map(NbtError::kind)
+}
+
+fn to_bytes(snbt: &str) -> Vec<u8> {
+    let mut root = from_snbt(snbt).unwrap();
+    root.name = "Level".to_string();
+    let mut buf = Vec::new();
+    write_nbt(&mut buf, &root).unwrap();
+    buf
+}
+
+const CHUNK: &str = r#"{
+    xPos: 3,
+    Entities: [{id: "minecraft:cow"}, {id: "minecraft:pig"}, {id: "minecraft:cow"}],
+    Sections: [{Y: 0b, Blocks: [B; 1b, 2b]}, {Y: 1b, Blocks: [B;]}],
+    Heightmap: [I; 64, 65],
+    Pos: [1.0d, 2.0d]
+}"#;
+
+#[test]
+fn events() {
+    let bytes = to_bytes("{a: 1b, b: [1s, 2s], c: {}}");
+    let events = NbtReader::new(bytes.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        events,
+        vec![
+            NbtEvent::BeginCompound("Level".to_string()),
+            NbtEvent::Field("a".to_string(), NbtValue::Byte(1)),
+            NbtEvent::BeginList {
+                name: "b".to_string(),
+                tag: 2,
+                len: 2
+            },
+            NbtEvent::Field(String::new(), NbtValue::Short(1)),
+            NbtEvent::Field(String::new(), NbtValue::Short(2)),
+            NbtEvent::End,
+            NbtEvent::BeginCompound("c".to_string()),
+            NbtEvent::End,
+            NbtEvent::End,
+        ]
+    );
+}
+
+#[test]
+fn count_entities() {
+    let bytes = to_bytes(CHUNK);
+    let mut reader = NbtReader::new(bytes.as_slice());
+    let mut cows = 0;
+    while let Some(event) = reader.next_event().unwrap() {
+        if let NbtEvent::Field(name, NbtValue::String(id)) = event {
+            if name == "id" && id == "minecraft:cow" {
+                cows += 1;
+            }
+        }
+    }
+    assert_eq!(cows, 2);
+    assert!(reader.next_event().unwrap().is_none());
+}
+
+#[test]
+fn skip_to_sections() {
+    let bytes = to_bytes(CHUNK);
+    let mut reader = NbtReader::new(bytes.as_slice());
+    let mut ys = Vec::new();
+    while let Some(event) = reader.next_event().unwrap() {
+        match event {
+            NbtEvent::BeginList { name, .. } if name == "Entities" => {
+                reader.skip_container().unwrap()
+            }
+            NbtEvent::BeginList { name, len, .. } if name == "Sections" => {
+                assert_eq!(len, 2);
+                assert_eq!(reader.depth(), 2);
+                while let Some(NbtEvent::BeginCompound(_)) = reader.next_event().unwrap() {
+                    match reader.next_event().unwrap() {
+                        Some(NbtEvent::Field(_, NbtValue::Byte(y))) => ys.push(y),
+                        other => panic!("unexpected {:?}", other),
+                    }
+                    reader.skip_container().unwrap();
+                }
+                assert_eq!(reader.depth(), 1);
+            }
+            NbtEvent::Field(name, _) => assert_ne!(name, "id"),
+            _ => {}
+        }
+    }
+    assert_eq!(ys, vec![0, 1]);
+}
+
+#[test]
+fn skip_whole_root() {
+    let mut bytes = to_bytes(CHUNK);
+    bytes.extend_from_slice(b"tail");
+    let mut reader = NbtReader::new(bytes.as_slice());
+    reader.skip_container().unwrap();
+    assert!(reader.next_event().unwrap().is_none());
+    assert_eq!(reader.into_inner(), b"tail");
+}
+
+/// Counts the calls to `read`.
+struct CountingReader<'a>(&'a [u8], usize);
+
+impl std::io::Read for CountingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.1 += 1;
+        self.0.read(buf)
+    }
+}
+
+#[test]
+fn skip_numbers_in_bulk() {
+    let root = NbtField::new_compound(
+        "",
+        vec![
+            NbtField {
+                name: "ints".to_string(),
+                value: NbtValue::List(NbtList::Int((0..10_000).collect())),
+            },
+            NbtField::new_long_array("longs", (0..10_000).collect()),
+            NbtField::new_i32("after", 7),
+        ],
+    );
+    for encoding in [Encoding::BigEndian, Encoding::NetworkLittleEndian] {
+        let mut bytes = Vec::new();
+        let options = WriteOptions::default().encoding(encoding);
+        write_nbt_with_options(&mut bytes, &root, &options).unwrap();
+
+        let options = ReadOptions::default().encoding(encoding);
+        let mut reader = NbtReader::with_options(CountingReader(&bytes, 0), &options);
+        reader.skip_container().unwrap();
+        let reads = reader.into_inner().1;
+        assert_eq!(reads < 100, encoding == Encoding::BigEndian, "{}", reads);
+
+        let mut reader = NbtReader::with_options(CountingReader(&bytes, 0), &options);
+        reader.next_event().unwrap();
+        assert!(matches!(
+            reader.next_event().unwrap(),
+            Some(NbtEvent::BeginList { len: 10_000, .. })
+        ));
+        reader.skip_container().unwrap();
+        assert!(matches!(
+            reader.next_event().unwrap(),
+            Some(NbtEvent::Field(_, NbtValue::LongArray(_)))
+        ));
+        assert_eq!(
+            reader.next_event().unwrap(),
+            Some(NbtEvent::Field("after".to_string(), NbtValue::Int(7)))
+        );
+    }
+}
+
+#[test]
+fn scalar_root() {
+    let mut bytes = Vec::new();
+    write_nbt(&mut bytes, &NbtField::new_i32("answer", 42)).unwrap();
+    let events = NbtReader::new(bytes.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        events,
+        vec![NbtEvent::Field("answer".to_string(), NbtValue::Int(42))]
+    );
+}
+
+#[test]
+fn errors() {
+    let bytes = to_bytes(CHUNK);
+    let result = NbtReader::new(&bytes[..bytes.len() - 3]).collect::<Result<Vec<_>, _>>();
+    assert!(matches!(kind(&result), Some(NbtError::UnexpectedEof)));
+
+    let mut reader = NbtReader::new(&bytes[..bytes.len() - 3]);
+    assert!(reader.skip_container().is_err());
+
+    let bytes = [10, 0, 0, 42, 0, 0];
+    let mut reader = NbtReader::new(&bytes[..]);
+    assert!(reader.next_event().is_ok());
+    assert!(matches!(
+        kind(&reader.next().unwrap()),
+        Some(NbtError::UnknownTag(42))
+    ));
+    assert!(reader.next().is_none());
+}
@@ -0,0 +1,310 @@
+use std::io::Cursor;
+
+use rnbt::*;
+
+const SECTOR_SIZE: usize = 4096;
+
+fn get_chunk(x: i32, z: i32) -> NbtField {
+    NbtField::new_compound(
+        "",
+        vec![
+            NbtField::new_i32("DataVersion", 3953),
+            NbtField::new_i32("xPos", x),
+            NbtField::new_i32("zPos", z),
+            NbtField::new_string("Status", "minecraft:full"),
+            NbtField::new_long_array("Heightmap", (0..37).collect()),
+        ],
+    )
+}
+
+/// Builds a region file by hand, placing the chunks in consecutive sectors after the header.
+fn build_region(chunks: &[(i32, i32, u8, Compression)]) -> Vec<u8> {
+    let mut buf = vec![0; 2 * SECTOR_SIZE];
+    for (i, (x, z, id, compression)) in chunks.iter().enumerate() {
+        let mut payload = Vec::new();
+        write_nbt_compressed(&mut payload, &get_chunk(*x, *z), *compression).unwrap();
+
+        let offset = buf.len() / SECTOR_SIZE;
+        buf.extend_from_slice(&(payload.len() as u32 + 1).to_be_bytes());
+        buf.push(*id);
+        buf.extend_from_slice(&payload);
+        buf.resize(buf.len().div_ceil(SECTOR_SIZE) * SECTOR_SIZE, 0);
+        let sectors = buf.len() / SECTOR_SIZE - offset;
+
+        let index = (*x as usize + *z as usize * 32) * 4;
+        let location = ((offset as u32) << 8) | sectors as u32;
+        buf[index..index + 4].copy_from_slice(&location.to_be_bytes());
+        let timestamp = 1_700_000_000 + i as u32;
+        buf[SECTOR_SIZE + index..SECTOR_SIZE + index + 4].copy_from_slice(&timestamp.to_be_bytes());
+    }
+    buf
+}
+
+#[test]
+fn read_chunks_with_all_compressions() {
+    let bytes = build_region(&[
+        (0, 0, 1, Compression::gzip()),
+        (1, 0, 2, Compression::zlib()),
+        (5, 7, 3, Compression::None),
+        (31, 31, 4, Compression::Lz4),
+    ]);
+    let mut region = Region::new(Cursor::new(bytes)).unwrap();
+
+    assert_eq!(
+        region.chunk_positions(),
+        vec![(0, 0), (1, 0), (5, 7), (31, 31)]
+    );
+    for (x, z) in region.chunk_positions() {
+        assert_eq!(region.read_chunk(x, z).unwrap(), Some(get_chunk(x, z)));
+    }
+    assert_eq!(region.read_chunk(2, 2).unwrap(), None);
+    assert!(!region.has_chunk(2, 2));
+    assert_eq!(region.timestamp(5, 7), Some(1_700_000_002));
+    assert_eq!(region.timestamp(2, 2), None);
+}
+
+#[test]
+fn absolute_chunk_coordinates() {
+    let bytes = build_region(&[(31, 1, 2, Compression::zlib())]);
+    let mut region = Region::new(Cursor::new(bytes)).unwrap();
+
+    // chunk (-1, 33) lives in r.-1.1.mca at local position (31, 1)
+    assert!(region.has_chunk(-1, 33));
+    assert_eq!(region.read_chunk(-1, 33).unwrap(), Some(get_chunk(31, 1)));
+}
+
+#[test]
+fn iterate_chunks() {
+    let bytes = build_region(&[
+        (3, 0, 2, Compression::zlib()),
+        (0, 4, 2, Compression::zlib()),
+    ]);
+    let mut region = Region::new(Cursor::new(bytes)).unwrap();
+
+    let chunks: Vec<_> = region.chunks().map(|c| c.unwrap()).collect();
+    assert_eq!(
+        chunks,
+        vec![(3, 0, get_chunk(3, 0)), (0, 4, get_chunk(0, 4))]
+    );
+}
+
+#[test]
+fn empty_region() {
+    let mut region = Region::new(Cursor::new(Vec::new())).unwrap();
+    assert!(region.chunk_positions().is_empty());
+    assert_eq!(region.read_chunk(0, 0).unwrap(), None);
+}
+
+#[test]
+fn unknown_chunk_compression() {
+    let mut bytes = build_region(&[(0, 0, 2, Compression::zlib())]);
+    bytes[2 * SECTOR_SIZE + 4] = 42;
+    let mut region = Region::new(Cursor::new(bytes)).unwrap();
+
+    assert!(matches!(
+        region.read_chunk(0, 0),
+        Err(NbtError::UnknownChunkCompression(42))
+    ));
+}
+
+fn region_len(region: Region<Cursor<Vec<u8>>>) -> usize {
+    region.into_inner().into_inner().len()
+}
+
+#[test]
+fn write_and_read_chunks() {
+    let mut region = Region::new(Cursor::new(Vec::new())).unwrap();
+    region
+        .write_chunk(0, 0, &get_chunk(0, 0), Compression::zlib())
+        .unwrap();
+    region
+        .write_chunk(4, 9, &get_chunk(4, 9), Compression::gzip())
+        .unwrap();
+    region
+        .write_chunk(8, 1, &get_chunk(8, 1), Compression::None)
+        .unwrap();
+    region
+        .write_chunk(31, 0, &get_chunk(31, 0), Compression::Lz4)
+        .unwrap();
+    assert!(region.timestamp(4, 9).unwrap() > 0);
+
+    // reopen from the raw bytes to make sure the header has been written correctly
+    let bytes = region.into_inner().into_inner();
+    assert_eq!(bytes.len() % SECTOR_SIZE, 0);
+    let mut region = Region::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(
+        region.chunk_positions(),
+        vec![(0, 0), (31, 0), (8, 1), (4, 9)]
+    );
+    for (x, z) in region.chunk_positions() {
+        assert_eq!(region.read_chunk(x, z).unwrap(), Some(get_chunk(x, z)));
+    }
+}
+
+#[test]
+fn rewrite_chunk_keeps_neighbours() {
+    let bytes = build_region(&[
+        (0, 0, 2, Compression::zlib()),
+        (1, 0, 2, Compression::zlib()),
+        (2, 0, 2, Compression::zlib()),
+    ]);
+    let untouched = bytes[3 * SECTOR_SIZE..4 * SECTOR_SIZE].to_vec();
+    let mut region = Region::new(Cursor::new(bytes)).unwrap();
+
+    // grows beyond its single sector and has to move to the end of the file
+    let big = NbtField::new_compound(
+        "",
+        vec![NbtField::new_long_array(
+            "data",
+            (0..2000).map(|i| i * 7919).collect(),
+        )],
+    );
+    region.write_chunk(0, 0, &big, Compression::None).unwrap();
+    assert_eq!(region.read_chunk(0, 0).unwrap(), Some(big));
+    assert_eq!(region.read_chunk(1, 0).unwrap(), Some(get_chunk(1, 0)));
+    assert_eq!(region.read_chunk(2, 0).unwrap(), Some(get_chunk(2, 0)));
+
+    let bytes = region.into_inner().into_inner();
+    assert_eq!(&bytes[3 * SECTOR_SIZE..4 * SECTOR_SIZE], &untouched[..]);
+}
+
+#[test]
+fn freed_sectors_are_reused() {
+    let mut region = Region::new(Cursor::new(Vec::new())).unwrap();
+    region
+        .write_chunk(0, 0, &get_chunk(0, 0), Compression::zlib())
+        .unwrap();
+    region
+        .write_chunk(1, 0, &get_chunk(1, 0), Compression::zlib())
+        .unwrap();
+    region.remove_chunk(0, 0).unwrap();
+    assert!(!region.has_chunk(0, 0));
+    assert_eq!(region.read_chunk(0, 0).unwrap(), None);
+
+    region
+        .write_chunk(2, 0, &get_chunk(2, 0), Compression::zlib())
+        .unwrap();
+    assert_eq!(region.read_chunk(2, 0).unwrap(), Some(get_chunk(2, 0)));
+    assert_eq!(region_len(region), 4 * SECTOR_SIZE);
+}
+
+#[test]
+fn locations_inside_the_header() {
+    let mut bytes = build_region(&[(0, 0, 2, Compression::zlib())]);
+    // chunk (1, 0) claims sectors 0 to 4, chunk (2, 0) sector 1
+    bytes[4..8].copy_from_slice(&5u32.to_be_bytes());
+    bytes[8..12].copy_from_slice(&0x101u32.to_be_bytes());
+    let mut region = Region::new(Cursor::new(bytes)).unwrap();
+    for x in [1, 2] {
+        assert!(matches!(
+            region.read_chunk(x, 0),
+            Err(NbtError::CorruptChunk(_))
+        ));
+    }
+
+    region
+        .write_chunk(1, 0, &get_chunk(1, 0), Compression::zlib())
+        .unwrap();
+    region
+        .write_chunk(3, 0, &get_chunk(3, 0), Compression::zlib())
+        .unwrap();
+    for x in [0, 1, 3] {
+        assert_eq!(region.read_chunk(x, 0).unwrap(), Some(get_chunk(x, 0)));
+    }
+    assert_eq!(region_len(region), 5 * SECTOR_SIZE);
+}
+
+#[test]
+fn write_region_file() {
+    let path = std::env::temp_dir().join(format!("rnbt-{}-r.0.0.mca", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut region = Region::open_or_create(&path).unwrap();
+    region
+        .write_chunk(3, 3, &get_chunk(3, 3), Compression::zlib())
+        .unwrap();
+    region.flush().unwrap();
+    drop(region);
+
+    let mut region = Region::open(&path).unwrap();
+    assert_eq!(region.read_chunk(3, 3).unwrap(), Some(get_chunk(3, 3)));
+    std::fs::remove_file(&path).unwrap();
+}
+
+fn get_oversized_chunk() -> NbtField {
+    NbtField::new_compound(
+        "",
+        vec![NbtField::new_long_array(
+            "data",
+            (0..140_000)
+                .map(|i: i64| i.wrapping_mul(0x5851_f42d_4c95_7f2d))
+                .collect(),
+        )],
+    )
+}
+
+fn temp_region_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("rnbt-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn oversized_chunk_spills_to_mcc() {
+    let dir = temp_region_dir("mcc");
+    let path = dir.join("r.-1.2.mca");
+    let mcc = dir.join("c.-29.68.mcc");
+
+    let mut region = Region::open_or_create(&path).unwrap();
+    region
+        .write_chunk(3, 4, &get_oversized_chunk(), Compression::None)
+        .unwrap();
+    assert!(mcc.exists());
+    assert_eq!(
+        region.read_chunk(3, 4).unwrap(),
+        Some(get_oversized_chunk())
+    );
+    drop(region);
+    assert_eq!(
+        std::fs::metadata(&path).unwrap().len(),
+        3 * SECTOR_SIZE as u64
+    );
+
+    let mut region = Region::open(&path).unwrap();
+    assert_eq!(
+        region.read_chunk(3, 4).unwrap(),
+        Some(get_oversized_chunk())
+    );
+    drop(region);
+
+    // shrinking the chunk moves it back into the region file
+    let mut region = Region::open_or_create(&path).unwrap();
+    region
+        .write_chunk(3, 4, &get_chunk(3, 4), Compression::zlib())
+        .unwrap();
+    assert!(!mcc.exists());
+    assert_eq!(region.read_chunk(3, 4).unwrap(), Some(get_chunk(3, 4)));
+
+    region
+        .write_chunk(3, 4, &get_oversized_chunk(), Compression::zlib())
+        .unwrap();
+    region
+        .write_chunk(3, 4, &get_oversized_chunk(), Compression::None)
+        .unwrap();
+    assert!(mcc.exists());
+    region.remove_chunk(3, 4).unwrap();
+    assert!(!mcc.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn oversized_chunk_without_directory() {
+    let mut region = Region::new(Cursor::new(Vec::new())).unwrap();
+    assert!(matches!(
+        region.write_chunk(0, 0, &get_oversized_chunk(), Compression::None),
+        Err(NbtError::ChunkTooLarge(_))
+    ));
+    assert!(!region.has_chunk(0, 0));
+}
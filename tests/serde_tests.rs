@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+
+use rnbt::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Level {
+    level_name: String,
+    hardcore: bool,
+    difficulty: i8,
+    spawn_x: i32,
+    random_seed: i64,
+    day_time: Option<i64>,
+    border_size: f64,
+    game_rules: BTreeMap<String, String>,
+    player: Player,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Player {
+    #[serde(rename = "Pos")]
+    pos: Vec<f64>,
+    #[serde(rename = "UUID", with = "rnbt::int_array")]
+    uuid: Vec<i32>,
+    #[serde(rename = "Inventory")]
+    inventory: Vec<Item>,
+    abilities: Abilities,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Item {
+    #[serde(rename = "Slot")]
+    slot: i8,
+    id: String,
+    count: u8,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Abilities {
+    flying: bool,
+    walk_speed: f32,
+}
+
+fn get_level() -> Level {
+    Level {
+        level_name: "New World".to_string(),
+        hardcore: false,
+        difficulty: 2,
+        spawn_x: -16,
+        random_seed: -4_172_144_997_891_116_613,
+        day_time: None,
+        border_size: 59_999_968.0,
+        game_rules: BTreeMap::from([
+            ("doDaylightCycle".to_string(), "true".to_string()),
+            ("keepInventory".to_string(), "false".to_string()),
+        ]),
+        player: Player {
+            pos: vec![0.5, 64.0, -3.25],
+            uuid: vec![1, -2, 3, -4],
+            inventory: vec![
+                Item {
+                    slot: 0,
+                    id: "minecraft:stone".to_string(),
+                    count: 64,
+                },
+                Item {
+                    slot: -106,
+                    id: "minecraft:shield".to_string(),
+                    count: 1,
+                },
+            ],
+            abilities: Abilities {
+                flying: true,
+                walk_speed: 0.1,
+            },
+        },
+    }
+}
+
+#[test]
+fn serialize_to_value() {
+    let root = to_value(&get_level()).unwrap();
+
+    assert_eq!(root.name, "");
+    assert_eq!(root.get_string("LevelName"), Some(&"New World".to_string()));
+    assert_eq!(root.get_byte("Hardcore"), Some(0));
+    assert_eq!(root.get_byte("Difficulty"), Some(2));
+    assert_eq!(
+        root.get_long("RandomSeed"),
+        Some(-4_172_144_997_891_116_613)
+    );
+    assert!(root.get("DayTime").is_none());
+
+    let player = root.get("Player").unwrap();
+    assert_eq!(
+        player.get_list("Pos"),
+        Some(&NbtList::Double(vec![0.5, 64.0, -3.25]))
+    );
+    assert_eq!(player.get_int_array("UUID"), Some(&vec![1, -2, 3, -4]));
+    assert_eq!(
+        player.get_path(&["abilities", "flying"]).unwrap().value,
+        NbtValue::Byte(1)
+    );
+    let inventory = player
+        .get_list("Inventory")
+        .unwrap()
+        .as_compound_list()
+        .unwrap();
+    assert_eq!(inventory[1].get_byte("Slot"), Some(-106i8 as u8));
+}
+
+#[test]
+fn serde_round_trip() {
+    let level = get_level();
+    let root = to_value(&level).unwrap();
+    assert_eq!(from_value::<Level>(&root).unwrap(), level);
+
+    let mut buf = Vec::new();
+    to_writer(&mut buf, &level).unwrap();
+    assert_eq!(from_bytes(&buf).unwrap(), root);
+    assert_eq!(from_reader::<_, Level>(&mut buf.as_slice()).unwrap(), level);
+
+    let mut compressed = Vec::new();
+    write_nbt_compressed(&mut compressed, &root, Compression::gzip()).unwrap();
+    assert_eq!(
+        from_reader::<_, Level>(&mut compressed.as_slice()).unwrap(),
+        level
+    );
+}
+
+#[test]
+fn deserialize_from_snbt() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Entity<'a> {
+        id: &'a str,
+        #[serde(rename = "OnGround")]
+        on_ground: bool,
+        #[serde(rename = "Invulnerable", default)]
+        invulnerable: bool,
+        #[serde(rename = "Motion")]
+        motion: [f64; 3],
+        #[serde(rename = "UUID")]
+        uuid: IntArray,
+        #[serde(rename = "Tags")]
+        tags: Vec<String>,
+        data: LongArray,
+    }
+
+    let root = from_snbt(
+        r#"{id:"minecraft:zombie",OnGround:1b,Motion:[0.0d,-0.08d,0.0d],
+            UUID:[I;1,2,3,4],Tags:["a","b"],data:[L;]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        from_value::<Entity>(&root).unwrap(),
+        Entity {
+            id: "minecraft:zombie",
+            on_ground: true,
+            invulnerable: false,
+            motion: [0.0, -0.08, 0.0],
+            uuid: IntArray(vec![1, 2, 3, 4]),
+            tags: vec!["a".to_string(), "b".to_string()],
+            data: LongArray(vec![]),
+        }
+    );
+}
+
+#[test]
+fn typed_arrays() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Arrays {
+        bytes: ByteArray,
+        ints: IntArray,
+        longs: LongArray,
+        #[serde(with = "rnbt::long_array")]
+        states: Vec<i64>,
+        list: Vec<i64>,
+    }
+
+    let arrays = Arrays {
+        bytes: ByteArray(vec![1, 255]),
+        ints: IntArray(vec![]),
+        longs: LongArray(vec![i64::MIN]),
+        states: vec![1, 2],
+        list: vec![1, 2],
+    };
+    let root = to_value(&arrays).unwrap();
+    assert_eq!(
+        root.value.to_snbt(),
+        "{bytes:[B;1b,-1b],ints:[I;],longs:[L;-9223372036854775808L],states:[L;1L,2L],list:[1L,2L]}"
+    );
+    assert_eq!(from_value::<Arrays>(&root).unwrap(), arrays);
+
+    // lists and arrays are interchangeable on read
+    let root = from_snbt("{bytes:[1b],ints:[1,2],longs:[],states:[3L],list:[L;4L]}").unwrap();
+    let arrays = from_value::<Arrays>(&root).unwrap();
+    assert_eq!(arrays.ints, IntArray(vec![1, 2]));
+    assert_eq!(arrays.list, vec![4]);
+}
+
+#[test]
+fn bool_from_byte() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Flags {
+        a: bool,
+        b: bool,
+        c: bool,
+    }
+
+    let root = from_snbt("{a:1b,b:0b,c:true}").unwrap();
+    assert_eq!(
+        from_value::<Flags>(&root).unwrap(),
+        Flags {
+            a: true,
+            b: false,
+            c: true
+        }
+    );
+    assert!(from_value::<Flags>(&from_snbt("{a:2b,b:0b,c:0b}").unwrap()).is_err());
+}
+
+#[test]
+fn enums() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f32),
+        Line(i32, i32),
+        Rect { w: i16, h: i16 },
+    }
+
+    let shapes = vec![
+        Shape::Empty,
+        Shape::Circle(1.5),
+        Shape::Line(1, 2),
+        Shape::Rect { w: 3, h: 4 },
+    ];
+    for shape in shapes {
+        let root = to_value(&shape).unwrap();
+        assert_eq!(from_value::<Shape>(&root).unwrap(), shape);
+    }
+    assert_eq!(
+        to_value(&Shape::Rect { w: 3, h: 4 }).unwrap().to_snbt(),
+        "{Rect:{w:3s,h:4s}}"
+    );
+    assert_eq!(to_value(&Shape::Empty).unwrap().to_snbt(), r#""Empty""#);
+}
+
+#[test]
+fn serialize_errors() {
+    assert!(matches!(
+        to_value(&Option::<i32>::None),
+        Err(NbtError::SerdeError(_))
+    ));
+    assert!(matches!(to_value(&u64::MAX), Err(NbtError::SerdeError(_))));
+    assert!(matches!(
+        to_value(&vec![Some(1), None]),
+        Err(NbtError::SerdeError(_))
+    ));
+
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum Mixed {
+        Int(i32),
+        Text(String),
+    }
+    assert!(matches!(
+        to_value(&vec![Mixed::Int(1), Mixed::Text("a".to_string())]),
+        Err(NbtError::SerdeError(_))
+    ));
+}
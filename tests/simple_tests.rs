@@ -11,6 +11,7 @@ fn read_write_test(field: NbtField) {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn pod_read_write() {
     read_write_test(NbtField {
         name: "test".to_string(),
@@ -89,28 +90,131 @@ fn list_read_write() {
         value: NbtValue::List(NbtList::Compound(vec![
             NbtField {
                 name: "".to_string(),
-                value: NbtValue::Compound(vec![
-                    NbtField {
-                        name: "int_a".to_string(),
-                        value: NbtValue::Int(1 >> 16),
-                    },
-                    NbtField {
-                        name: "int_b".to_string(),
-                        value: NbtValue::Int(42 >> 16),
-                    },
-                ]),
+                value: NbtValue::Compound(
+                    vec![
+                        NbtField {
+                            name: "int_a".to_string(),
+                            value: NbtValue::Int(1 >> 16),
+                        },
+                        NbtField {
+                            name: "int_b".to_string(),
+                            value: NbtValue::Int(42 >> 16),
+                        },
+                    ]
+                    .into(),
+                ),
             },
             NbtField {
                 name: "".to_string(),
-                value: NbtValue::Compound(vec![NbtField {
-                    name: "float".to_string(),
-                    value: NbtValue::Float(1.0),
-                }]),
+                value: NbtValue::Compound(
+                    vec![NbtField {
+                        name: "float".to_string(),
+                        value: NbtValue::Float(1.0),
+                    }]
+                    .into(),
+                ),
             },
         ])),
     });
 }
 
+fn list_field(list: NbtList) -> NbtField {
+    NbtField {
+        name: "test".to_string(),
+        value: NbtValue::List(list),
+    }
+}
+
+/// Writes `field`, reads it back and checks that writing it again gives the same bytes.
+fn byte_round_trip(field: NbtField) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_nbt(&mut buf, &field).unwrap();
+    let read = read_nbt(&mut buf.as_slice()).unwrap();
+    assert_eq!(field, read);
+    let mut again = Vec::new();
+    write_nbt(&mut again, &read).unwrap();
+    assert_eq!(buf, again);
+    buf
+}
+
+#[test]
+fn nested_list_read_write() {
+    byte_round_trip(list_field(NbtList::List(vec![
+        NbtValue::List(NbtList::Int(vec![1, 2])),
+        NbtValue::List(NbtList::String(vec!["a".to_owned()])),
+        NbtValue::List(NbtList::End),
+        NbtValue::List(NbtList::List(vec![NbtValue::List(NbtList::Double(vec![
+            0.5,
+        ]))])),
+    ])));
+    byte_round_trip(list_field(NbtList::ByteArray(vec![vec![1, 2], vec![]])));
+    byte_round_trip(list_field(NbtList::IntArray(vec![
+        vec![1, -1],
+        vec![],
+        vec![3],
+    ])));
+    byte_round_trip(list_field(NbtList::LongArray(vec![vec![i64::MIN], vec![]])));
+    byte_round_trip(list_field(NbtList::List(Vec::new())));
+    byte_round_trip(list_field(NbtList::IntArray(Vec::new())));
+}
+
+#[test]
+fn empty_list_read_write() {
+    let bytes = byte_round_trip(list_field(NbtList::End));
+    assert_eq!(bytes, [9, 0, 4, b't', b'e', b's', b't', 0, 0, 0, 0, 0]);
+
+    let compound = NbtField {
+        name: String::new(),
+        value: NbtValue::Compound(
+            vec![
+                NbtField::new_i32("before", 1),
+                list_field(NbtList::End),
+                NbtField::new_i32("after", 2),
+            ]
+            .into(),
+        ),
+    };
+    byte_round_trip(compound);
+}
+
+#[test]
+fn every_list_type_round_trips() {
+    let lists = [
+        NbtList::Byte(vec![0, 255]),
+        NbtList::Short(vec![i16::MIN, 7]),
+        NbtList::Int(vec![i32::MAX]),
+        NbtList::Long(vec![-1]),
+        NbtList::Float(vec![1.5]),
+        NbtList::Double(vec![-2.25]),
+        NbtList::String(vec![String::new(), "x".to_owned()]),
+        NbtList::List(vec![NbtValue::List(NbtList::Byte(vec![1]))]),
+        NbtList::Compound(vec![NbtField {
+            name: String::new(),
+            value: NbtValue::Compound(vec![NbtField::new_i32("a", 1)].into()),
+        }]),
+        NbtList::ByteArray(vec![vec![4, 5]]),
+        NbtList::IntArray(vec![vec![6]]),
+        NbtList::LongArray(vec![vec![7, 8]]),
+        NbtList::End,
+    ];
+    let root = NbtField {
+        name: "root".to_string(),
+        value: NbtValue::Compound(
+            lists
+                .iter()
+                .enumerate()
+                .map(|(i, list)| NbtField {
+                    name: i.to_string(),
+                    value: NbtValue::List(list.clone()),
+                })
+                .collect(),
+        ),
+    };
+    let bytes = byte_round_trip(root.clone());
+    assert_eq!(NbtRef::new(&bytes).unwrap().to_field().unwrap(), root);
+    assert_eq!(from_snbt(&root.to_snbt()).unwrap().value, root.value);
+}
+
 #[test]
 fn compound_read_write() {
     let compound = vec![
@@ -126,7 +230,7 @@ fn compound_read_write() {
 
     read_write_test(NbtField {
         name: "test".to_string(),
-        value: NbtValue::Compound(compound),
+        value: NbtValue::Compound(compound.into()),
     });
 }
 
@@ -171,7 +275,7 @@ fn compound_access() {
 
     let root = NbtField {
         name: "test".to_string(),
-        value: NbtValue::Compound(compound),
+        value: NbtValue::Compound(compound.into()),
     };
 
     assert_eq!(
@@ -397,3 +501,44 @@ fn field_access() {
         Some(&vec!["1".to_owned(), "2".to_owned(), "3".to_owned()])
     );
 }
+
+#[test]
+fn boolean_read_write() {
+    let root = NbtField::new_compound(
+        "",
+        vec![
+            NbtField::new_bool("yes", true),
+            NbtField::new_bool("no", false),
+            NbtField {
+                name: "flags".to_string(),
+                value: NbtValue::List(NbtList::Boolean(vec![true, false])),
+            },
+        ],
+    );
+    read_write_test(root.clone());
+    assert_eq!(root.get_bool("yes"), Some(true));
+    assert_eq!(root.get_bool("no"), Some(false));
+    assert_eq!(root.get_byte("yes"), Some(1));
+
+    // booleans are written as bytes
+    let bytes = from_snbt("{yes:1b,no:0b,flags:[1b,0b]}").unwrap();
+    let (mut buf, mut expected) = (Vec::new(), Vec::new());
+    write_nbt(&mut buf, &root).unwrap();
+    write_nbt(&mut expected, &bytes).unwrap();
+    assert_eq!(buf, expected);
+}
+
+#[test]
+fn boolean_equality() {
+    assert_eq!(NbtValue::Boolean(true), NbtValue::Byte(1));
+    assert_eq!(NbtValue::Byte(0), NbtValue::Boolean(false));
+    assert_ne!(NbtValue::Boolean(true), NbtValue::Byte(2));
+    assert_ne!(NbtValue::Boolean(false), NbtValue::Int(0));
+    assert_eq!(NbtValue::Byte(2).as_bool(), None);
+    assert_eq!(NbtValue::Byte(1).as_bool(), Some(true));
+    assert_eq!(
+        NbtList::Byte(vec![1, 0]),
+        NbtList::Boolean(vec![true, false])
+    );
+    assert_ne!(NbtList::Byte(vec![1]), NbtList::Boolean(vec![true, false]));
+}
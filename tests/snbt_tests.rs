@@ -0,0 +1,273 @@
+use rnbt::*;
+
+fn parse(snbt: &str) -> NbtValue {
+    snbt.parse().unwrap()
+}
+
+#[test]
+fn parse_item() {
+    let item = from_snbt(r#"{Count:1b,id:"minecraft:stone",tag:{Damage:0s}}"#).unwrap();
+    assert_eq!(
+        item,
+        NbtField::new_compound(
+            "",
+            vec![
+                NbtField {
+                    name: "Count".to_string(),
+                    value: NbtValue::Byte(1),
+                },
+                NbtField::new_string("id", "minecraft:stone"),
+                NbtField::new_compound("tag", vec![NbtField::new_short("Damage", 0)]),
+            ],
+        )
+    );
+}
+
+#[test]
+fn parse_numbers() {
+    assert_eq!(parse("1b"), NbtValue::Byte(1));
+    assert_eq!(parse("-1B"), NbtValue::Byte(255));
+    assert_eq!(parse("-300s"), NbtValue::Short(-300));
+    assert_eq!(parse("42"), NbtValue::Int(42));
+    assert_eq!(parse("+42"), NbtValue::Int(42));
+    assert_eq!(parse("9000000000L"), NbtValue::Long(9_000_000_000));
+    assert_eq!(parse("1.5f"), NbtValue::Float(1.5));
+    assert_eq!(parse("3F"), NbtValue::Float(3.0));
+    assert_eq!(parse("1.5"), NbtValue::Double(1.5));
+    assert_eq!(parse(".5"), NbtValue::Double(0.5));
+    assert_eq!(parse("2."), NbtValue::Double(2.0));
+    assert_eq!(parse("2d"), NbtValue::Double(2.0));
+    assert_eq!(parse("1.5e3D"), NbtValue::Double(1500.0));
+    assert_eq!(parse("true"), NbtValue::Boolean(true));
+    assert_eq!(parse("false"), NbtValue::Boolean(false));
+}
+
+#[test]
+fn parse_unquoted_strings() {
+    // values that look almost like numbers stay strings, as in Minecraft
+    assert_eq!(parse("300b"), NbtValue::String("300b".to_string()));
+    assert_eq!(parse("1e5"), NbtValue::String("1e5".to_string()));
+    assert_eq!(
+        parse("9999999999"),
+        NbtValue::String("9999999999".to_string())
+    );
+    assert_eq!(parse("stone"), NbtValue::String("stone".to_string()));
+    assert_eq!(
+        parse("a.b-c_d+e"),
+        NbtValue::String("a.b-c_d+e".to_string())
+    );
+}
+
+#[test]
+fn parse_quoted_strings() {
+    assert_eq!(
+        parse(r#""hello world""#),
+        NbtValue::String("hello world".to_string())
+    );
+    assert_eq!(
+        parse(r#"'say "hi"'"#),
+        NbtValue::String("say \"hi\"".to_string())
+    );
+    assert_eq!(parse(r#""it's""#), NbtValue::String("it's".to_string()));
+    assert_eq!(
+        parse(r#""a\"b\\c\'d\né""#),
+        NbtValue::String("a\"b\\c'd\né".to_string())
+    );
+    assert_eq!(parse(r#"'\''"#), NbtValue::String("'".to_string()));
+    assert_eq!(
+        parse(r#""☃ ünïcödé""#),
+        NbtValue::String("☃ ünïcödé".to_string())
+    );
+}
+
+#[test]
+fn parse_keys() {
+    let value = parse(r#"{ "quoted key" : 1, 'single':2 ,plain_key.x: 3, 4:4}"#);
+    assert_eq!(
+        value,
+        NbtValue::Compound(
+            vec![
+                NbtField::new_i32("quoted key", 1),
+                NbtField::new_i32("single", 2),
+                NbtField::new_i32("plain_key.x", 3),
+                NbtField::new_i32("4", 4),
+            ]
+            .into()
+        )
+    );
+    assert_eq!(parse("{}"), NbtValue::Compound(NbtCompound::new()));
+    assert_eq!(
+        parse("\u{3000}{}\u{3000}"),
+        NbtValue::Compound(NbtCompound::new())
+    );
+}
+
+#[test]
+fn parse_typed_arrays() {
+    assert_eq!(
+        parse("[B;1b,-2b,true]"),
+        NbtValue::ByteArray(vec![1, 254, 1])
+    );
+    assert_eq!(parse("[I; 1, 2, 3]"), NbtValue::IntArray(vec![1, 2, 3]));
+    assert_eq!(parse("[L;1l,-2L]"), NbtValue::LongArray(vec![1, -2]));
+    assert_eq!(parse("[I;]"), NbtValue::IntArray(vec![]));
+}
+
+#[test]
+fn parse_lists() {
+    assert_eq!(parse("[]"), NbtValue::List(NbtList::End));
+    assert_eq!(parse("[1b, 2b]"), NbtValue::List(NbtList::Byte(vec![1, 2])));
+    assert_eq!(
+        parse("[1b, true]"),
+        NbtValue::List(NbtList::Byte(vec![1, 1]))
+    );
+    assert_eq!(
+        parse("[0.5d, 64.0d, 0.5d]"),
+        NbtValue::List(NbtList::Double(vec![0.5, 64.0, 0.5]))
+    );
+    assert_eq!(
+        parse(r#"[a, "b c"]"#),
+        NbtValue::List(NbtList::String(vec!["a".to_string(), "b c".to_string()]))
+    );
+    assert_eq!(
+        parse("[[1, 2], []]"),
+        NbtValue::List(NbtList::List(vec![
+            NbtValue::List(NbtList::Int(vec![1, 2])),
+            NbtValue::List(NbtList::End),
+        ]))
+    );
+    assert_eq!(
+        parse("[{Slot:0b}, {}]"),
+        NbtValue::List(NbtList::Compound(vec![
+            NbtField::new_compound(
+                "",
+                vec![NbtField {
+                    name: "Slot".to_string(),
+                    value: NbtValue::Byte(0),
+                }]
+            ),
+            NbtField::new_compound("", vec![]),
+        ]))
+    );
+    assert_eq!(
+        parse("[[I;1],[I;2,3]]"),
+        NbtValue::List(NbtList::IntArray(vec![vec![1], vec![2, 3]]))
+    );
+}
+
+fn parse_error(snbt: &str) -> usize {
+    match snbt.parse::<NbtValue>() {
+        Err(NbtError::SnbtParseError { position, .. }) => position,
+        other => panic!("expected parse error for {}, got {:?}", snbt, other),
+    }
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(parse_error("{a:1"), 4);
+    assert_eq!(parse_error("{a 1}"), 3);
+    assert_eq!(parse_error("[1, 2b]"), 1);
+    assert_eq!(parse_error("[I; 1, 2b]"), 7);
+    assert_eq!(parse_error(r#""unterminated"#), 13);
+    assert_eq!(parse_error(r#""bad \q escape""#), 5);
+    assert_eq!(parse_error("{a:1} trailing"), 6);
+    assert_eq!(parse_error(""), 0);
+    assert_eq!(parse_error("{:1}"), 1);
+    parse_error(&"[".repeat(1000));
+}
+
+fn get_full_value() -> NbtValue {
+    parse(
+        r#"{
+            byte: -1b, bool: true, short: 300s, int: -7, long: 9000000000L,
+            float: 1.5f, double: 0.1d, string: "say \"hi\"", 'key with spaces': "it's",
+            bytes: [B; 1b, -2b], ints: [I; 1, 2], longs: [L; 3L],
+            list: [1.0f, 2.5f], strings: ["a", 'b"'], nested: [[1s], []],
+            compounds: [{id: "minecraft:stone", Count: 1b}, {}],
+            int_arrays: [[I; 1], [I;]], empty: {}, "": 0
+        }"#,
+    )
+}
+
+#[test]
+fn write_snbt_compact() {
+    assert_eq!(NbtValue::Byte(255).to_snbt(), "-1b");
+    assert_eq!(NbtValue::Short(3).to_snbt(), "3s");
+    assert_eq!(NbtValue::Int(3).to_snbt(), "3");
+    assert_eq!(NbtValue::Long(3).to_snbt(), "3L");
+    assert_eq!(NbtValue::Float(1.0).to_snbt(), "1f");
+    assert_eq!(NbtValue::Double(0.25).to_snbt(), "0.25d");
+    assert_eq!(NbtValue::Boolean(false).to_snbt(), "false");
+    assert_eq!(
+        NbtValue::String(r#"a"b\c"#.to_string()).to_snbt(),
+        r#"'a"b\\c'"#
+    );
+    assert_eq!(
+        NbtValue::String(r#"it's "x""#.to_string()).to_snbt(),
+        r#""it's \"x\"""#
+    );
+    assert_eq!(NbtValue::ByteArray(vec![1, 255]).to_snbt(), "[B;1b,-1b]");
+    assert_eq!(NbtValue::IntArray(vec![1, 2]).to_snbt(), "[I;1,2]");
+    assert_eq!(NbtValue::LongArray(vec![]).to_snbt(), "[L;]");
+    assert_eq!(
+        NbtValue::List(NbtList::String(vec!["a b".to_string()])).to_snbt(),
+        r#"["a b"]"#
+    );
+    assert_eq!(NbtValue::List(NbtList::End).to_snbt(), "[]");
+
+    let item = from_snbt(r#"{Count:1b,id:"minecraft:stone",tag:{Damage:0s,"a b":[]}}"#).unwrap();
+    assert_eq!(
+        item.to_snbt(),
+        r#"{Count:1b,id:"minecraft:stone",tag:{Damage:0s,"a b":[]}}"#
+    );
+    assert_eq!(format!("{}", item.value), item.to_snbt());
+    assert_eq!(format!("{}", NbtField::new_i32("x y", 1)), r#""x y":1"#);
+}
+
+#[test]
+fn write_snbt_pretty() {
+    let value = parse(r#"{a:1,b:[{c:[1b,2b]},{}],d:[I;1,2],e:{}}"#);
+    assert_eq!(
+        value.to_snbt_pretty(),
+        r#"{
+    a: 1,
+    b: [
+        {
+            c: [1b, 2b]
+        },
+        {}
+    ],
+    d: [I; 1, 2],
+    e: {}
+}"#
+    );
+    assert_eq!(format!("{:#}", value), value.to_snbt_pretty());
+}
+
+#[test]
+fn snbt_round_trip() {
+    let value = get_full_value();
+    assert_eq!(parse(&value.to_snbt()), value);
+    assert_eq!(parse(&value.to_snbt_pretty()), value);
+    assert_eq!(parse(&format!("{}", value)), value);
+}
+
+#[test]
+fn snbt_lossy_values() {
+    assert_eq!(NbtValue::Float(f32::NAN).to_snbt(), "NaNf");
+    assert_eq!(NbtValue::Double(f64::INFINITY).to_snbt(), "infd");
+    assert_eq!(NbtValue::Float(f32::NEG_INFINITY).to_snbt(), "-inff");
+    assert_eq!(parse("NaNf"), NbtValue::String("NaNf".to_string()));
+    assert_eq!(parse("infd"), NbtValue::String("infd".to_string()));
+
+    let end = NbtField::new_compound(
+        "",
+        vec![NbtField {
+            name: "x".to_string(),
+            value: NbtValue::End,
+        }],
+    );
+    assert_eq!(NbtValue::End.to_snbt(), "");
+    assert_eq!(end.to_snbt(), "{x:}");
+    assert!(from_snbt(&end.to_snbt()).is_err());
+}
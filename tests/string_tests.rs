@@ -0,0 +1,99 @@
+use rnbt::*;
+
+/// "a\0b😀" as written by Java: NUL as `C0 80`, the emoji as a surrogate pair.
+const JAVA: [u8; 11] = [
+    b'a', 0xc0, 0x80, b'b', 0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80, b'!',
+];
+
+fn string_field(bytes: &[u8]) -> Vec<u8> {
+    let mut field = vec![8, 0, 0, 0, bytes.len() as u8];
+    field.extend_from_slice(bytes);
+    field
+}
+
+#[test]
+fn modified_utf8_round_trip() {
+    let field = NbtField::new_string("na\0me😀", "a\0b😀!");
+    let mut bytes = Vec::new();
+    write_nbt(&mut bytes, &field).unwrap();
+    assert!(bytes.ends_with(&JAVA));
+    assert!(!bytes.contains(&0xf0));
+    assert_eq!(from_bytes(&bytes).unwrap(), field);
+
+    let view = NbtRef::new(&bytes).unwrap();
+    assert_eq!(view.name(), "na\0me😀");
+    assert_eq!(view.as_str().unwrap(), "a\0b😀!");
+    assert_eq!(
+        NbtReader::new(bytes.as_slice()).next().unwrap().unwrap(),
+        NbtEvent::Field(
+            "na\0me😀".to_string(),
+            NbtValue::String("a\0b😀!".to_string())
+        )
+    );
+
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.field_string("na\0me😀", "a\0b😀!").unwrap();
+    assert_eq!(writer.finish().unwrap(), bytes);
+}
+
+#[test]
+fn decode_java_strings() {
+    let bytes = string_field(&JAVA);
+    assert_eq!(
+        from_bytes(&bytes).unwrap().value,
+        NbtValue::String("a\0b😀!".to_string())
+    );
+
+    // plain UTF-8 is only the same encoding without NUL and supplementary characters
+    let bytes = string_field("größe".as_bytes());
+    assert_eq!(NbtRef::new(&bytes).unwrap().as_str().unwrap(), "größe");
+}
+
+#[test]
+fn strict_and_lenient_decoding() {
+    let lenient = ReadOptions::default().lenient_strings(true);
+
+    // standard UTF-8 for a supplementary character, as written by some other tools
+    let bytes = string_field("x😀".as_bytes());
+    assert!(matches!(
+        from_bytes(&bytes).unwrap_err().kind(),
+        NbtError::Mutf8Error(1)
+    ));
+    assert!(matches!(NbtRef::new(&bytes), Err(NbtError::Mutf8Error(1))));
+    assert_eq!(
+        from_bytes_with_options(&bytes, &lenient).unwrap().value,
+        NbtValue::String("x😀".to_string())
+    );
+    let view = NbtRef::with_options(&bytes, &lenient).unwrap();
+    assert_eq!(view.as_str().unwrap(), "x😀");
+
+    // an unpaired surrogate and a truncated sequence
+    let bytes = string_field(&[0xed, 0xa0, 0xbd, b'a', 0xc3]);
+    assert!(from_bytes(&bytes).is_err());
+    assert_eq!(
+        from_bytes_with_options(&bytes, &lenient).unwrap().value,
+        NbtValue::String("\u{fffd}a\u{fffd}".to_string())
+    );
+    assert_eq!(
+        NbtRef::with_options(&bytes, &lenient)
+            .unwrap()
+            .to_value()
+            .unwrap(),
+        NbtValue::String("\u{fffd}a\u{fffd}".to_string())
+    );
+}
+
+#[test]
+fn encoded_length_limit() {
+    // 10923 emoji take 43692 bytes as UTF-8 but 65538 bytes as modified UTF-8
+    let long = "😀".repeat(10923);
+    let mut writer = NbtWriter::new(Vec::new());
+    assert!(writer.field_string("s", &long).is_err());
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.field_string("s", &long[4..]).unwrap();
+    let bytes = writer.finish().unwrap();
+    assert_eq!(
+        from_bytes(&bytes).unwrap().value,
+        NbtValue::String(long[4..].to_string())
+    );
+}
@@ -0,0 +1,154 @@
+use rnbt::*;
+
+fn page(len: usize) -> NbtValue {
+    NbtValue::String("x".repeat(len))
+}
+
+#[test]
+fn string_too_long() {
+    let book = NbtField::new_compound(
+        "",
+        vec![NbtField {
+            name: "pages".to_string(),
+            value: NbtValue::List(NbtList::String(vec!["ok".to_string(), "y".repeat(70_000)])),
+        }],
+    );
+    let mut buf = Vec::new();
+    let err = write_nbt(&mut buf, &book).unwrap_err();
+    assert!(matches!(
+        &err,
+        NbtError::StringTooLong { path, len: 70_000 } if path == "pages[1]"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "string of 70000 bytes is too long in pages[1]"
+    );
+    assert!(buf.is_empty());
+
+    let limit = NbtField::new_compound(
+        "",
+        vec![NbtField {
+            name: "page".to_string(),
+            value: page(u16::MAX as usize),
+        }],
+    );
+    assert!(limit.validate().is_ok());
+    write_nbt(&mut buf, &limit).unwrap();
+    assert_eq!(from_bytes(&buf).unwrap(), limit);
+
+    let long_name = NbtField {
+        name: "n".repeat(65_536),
+        value: NbtValue::Int(0),
+    };
+    assert!(matches!(
+        long_name.validate().unwrap_err().as_slice(),
+        [NbtError::StringTooLong { len: 65_536, .. }]
+    ));
+}
+
+#[test]
+fn mixed_lists() {
+    let root = NbtField::new_compound(
+        "",
+        vec![
+            NbtField {
+                name: "lists".to_string(),
+                value: NbtValue::List(NbtList::List(vec![
+                    NbtValue::List(NbtList::Int(vec![1])),
+                    NbtValue::Int(2),
+                ])),
+            },
+            NbtField {
+                name: "items".to_string(),
+                value: NbtValue::List(NbtList::Compound(vec![NbtField::new_i32("", 3)])),
+            },
+        ],
+    );
+    let mut buf = Vec::new();
+    assert!(matches!(
+        write_nbt(&mut buf, &root),
+        Err(NbtError::MixedList { path, expected: TAG_LIST, found: TAG_INT }) if path == "lists[1]"
+    ));
+    assert!(buf.is_empty());
+    let problems = root.validate().unwrap_err();
+    assert_eq!(problems.len(), 2);
+    assert!(matches!(
+        &problems[1],
+        NbtError::MixedList { path, expected: TAG_COMPOUND, found: TAG_INT } if path == "items[0]"
+    ));
+}
+
+#[test]
+fn validate_reports_every_problem() {
+    let root = NbtField::new_compound(
+        "",
+        vec![
+            NbtField {
+                name: "a".to_string(),
+                value: page(65_536),
+            },
+            NbtField::new_compound(
+                "nested name",
+                vec![NbtField {
+                    name: "b".to_string(),
+                    value: page(100_000),
+                }],
+            ),
+            NbtField {
+                name: "c".to_string(),
+                value: page(10),
+            },
+        ],
+    );
+    let paths = root
+        .validate()
+        .unwrap_err()
+        .iter()
+        .map(|e| match e {
+            NbtError::StringTooLong { path, .. } => path.clone(),
+            e => panic!("unexpected problem {:?}", e),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(paths, vec!["a", "\"nested name\".b"]);
+    assert!(from_snbt("{a:[[1],[\"b\"]],c:[{}]}")
+        .unwrap()
+        .validate()
+        .is_ok());
+}
+
+#[test]
+fn misplaced_end() {
+    let root = NbtField::new_compound(
+        "",
+        vec![
+            NbtField {
+                name: "x".to_string(),
+                value: NbtValue::End,
+            },
+            NbtField::new_i32("y", 1),
+        ],
+    );
+    let mut buf = Vec::new();
+    let err = write_nbt(&mut buf, &root).unwrap_err();
+    assert!(matches!(&err, NbtError::MisplacedEnd { path } if path == "x"));
+    assert_eq!(
+        err.to_string(),
+        "TAG_End can only be written as an empty root in x"
+    );
+    assert!(buf.is_empty());
+
+    let mut nested = from_snbt("{items:[{id:1}]}").unwrap();
+    *nested.get_indexed_path_mut(&["items", "0", "id"]).unwrap() = NbtValue::End;
+    assert!(matches!(
+        nested.validate().unwrap_err().as_slice(),
+        [NbtError::MisplacedEnd { path }] if path == "items[0].id"
+    ));
+
+    let empty = NbtField {
+        name: String::new(),
+        value: NbtValue::End,
+    };
+    assert!(empty.validate().is_ok());
+    write_nbt(&mut buf, &empty).unwrap();
+    assert_eq!(buf, [0]);
+}
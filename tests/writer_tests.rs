@@ -0,0 +1,89 @@
+use rnbt::*;
+
+#[test]
+fn write_structure() {
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.begin_compound("").unwrap();
+    writer.field_int("DataVersion", 3465).unwrap();
+    writer.begin_list("size", TAG_INT, 3).unwrap();
+    for v in [16, 8, 16] {
+        writer.field_int("", v).unwrap();
+    }
+    writer.end().unwrap();
+    writer.begin_list("blocks", TAG_COMPOUND, 2).unwrap();
+    for (i, state) in [0, 1].into_iter().enumerate() {
+        writer.begin_compound("").unwrap();
+        writer.field_int_array("pos", &[i as i32, 0, 0]).unwrap();
+        writer.field_int("state", state).unwrap();
+        writer.end().unwrap();
+    }
+    writer.end().unwrap();
+    writer.begin_list("palette", TAG_STRING, 2).unwrap();
+    writer.field_string("", "minecraft:air").unwrap();
+    writer.field_string("", "minecraft:stone").unwrap();
+    writer.end().unwrap();
+    writer.field_byte("b", 255).unwrap();
+    writer.field_short("s", -2).unwrap();
+    writer.field_long("l", i64::MIN).unwrap();
+    writer.field_float("f", 0.5).unwrap();
+    writer.field_double("d", -0.25).unwrap();
+    writer.field_byte_array("ba", &[1, 2]).unwrap();
+    writer.field_long_array("la", &[3]).unwrap();
+    writer.end().unwrap();
+    let bytes = writer.finish().unwrap();
+
+    let expected = from_snbt(
+        r#"{DataVersion:3465,size:[16,8,16],
+            blocks:[{pos:[I;0,0,0],state:0},{pos:[I;1,0,0],state:1}],
+            palette:["minecraft:air","minecraft:stone"],
+            b:-1b,s:-2s,l:-9223372036854775808L,f:0.5f,d:-0.25d,ba:[B;1b,2b],la:[L;3L]}"#,
+    )
+    .unwrap();
+    assert_eq!(from_bytes(&bytes).unwrap(), expected);
+
+    let mut tree = Vec::new();
+    write_nbt(&mut tree, &expected).unwrap();
+    assert_eq!(bytes, tree);
+}
+
+#[test]
+fn scalar_root() {
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.field_string("name", "value").unwrap();
+    assert_eq!(writer.depth(), 0);
+    let bytes = writer.finish().unwrap();
+    assert_eq!(
+        from_bytes(&bytes).unwrap(),
+        NbtField::new_string("name", "value")
+    );
+}
+
+#[test]
+fn invalid_nesting() {
+    let mut writer = NbtWriter::new(Vec::new());
+    assert!(matches!(writer.end(), Err(NbtError::InvalidWrite(_))));
+    writer.begin_compound("").unwrap();
+    writer.begin_list("l", TAG_SHORT, 2).unwrap();
+    assert!(writer.field_int("", 1).is_err());
+    writer.field_short("", 1).unwrap();
+    assert!(writer.end().is_err());
+    writer.field_short("", 2).unwrap();
+    assert!(writer.field_short("", 3).is_err());
+    writer.end().unwrap();
+    assert!(writer.begin_list("e", TAG_END, 1).is_err());
+    assert!(matches!(
+        writer.begin_list("e", 13, 0),
+        Err(NbtError::UnknownTag(13))
+    ));
+    writer.begin_list("e", TAG_END, 0).unwrap();
+    writer.end().unwrap();
+    assert_eq!(writer.depth(), 1);
+    writer.end().unwrap();
+    assert!(writer.field_byte("second root", 0).is_err());
+    assert!(writer.finish().is_ok());
+
+    let mut writer = NbtWriter::new(Vec::new());
+    writer.begin_compound("").unwrap();
+    assert!(writer.finish().is_err());
+    assert!(NbtWriter::new(Vec::new()).finish().is_err());
+}